@@ -0,0 +1,316 @@
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock, mpsc};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use rustc_serialize::json;
+use rustc_serialize::json::Json;
+use time::PreciseTime;
+
+// allowed values of the vehicle and metric columns
+const VEHICLES: [&'static str; 3] = ["car", "bike", "walk"];
+const METRICS: [&'static str; 2] = ["time", "distance"];
+
+pub struct BatchConfig {
+	pub threads: usize,
+	pub geometry: bool,
+	// used for rows without vehicle / metric column
+	pub vehicle: String,
+	pub metric: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct BatchRequest {
+	pub id: String,
+	pub source: ::data::Position,
+	pub target: ::data::Position,
+	pub vehicle: String,
+	pub metric: String,
+}
+
+#[derive(Debug, Clone, RustcEncodable)]
+pub struct BatchResult {
+	pub id: String,
+	pub distance: Option<f64>,
+	pub time: Option<f64>,
+	pub path: Option<Vec<[f64; 2]>>,
+}
+
+// input and output format are chosen by extension, .jsonl / .json means one json object per line, everything else is csv
+pub fn run(state: ::data::State, input: &str, output: &str, config: &BatchConfig) -> Result<(), String> {
+	let requests = read_requests(input, config).map_err(|e| format!("can't read {}: {}", input, e))?;
+	println!("[BATCH] routing {} requests with {} threads", requests.len(), config.threads);
+
+	let start = PreciseTime::now();
+	let results = route_all(Arc::new(state), requests, config);
+	let end = PreciseTime::now();
+
+	let found = results.iter().filter(|result| result.distance.is_some()).count();
+	println!("[BATCH] found {} of {} routes in {} ms", found, results.len(), start.to(end).num_milliseconds());
+
+	write_results(output, &results).map_err(|e| format!("can't write {}: {}", output, e))
+}
+
+fn is_json(filename: &str) -> bool {
+	filename.ends_with(".jsonl") || filename.ends_with(".json")
+}
+
+pub fn read_requests(filename: &str, config: &BatchConfig) -> io::Result<Vec<BatchRequest>> {
+	let reader = BufReader::new(File::open(filename)?);
+	if is_json(filename) {
+		parse_jsonl(reader, config)
+	} else {
+		parse_csv(reader, config)
+	}
+}
+
+// the header row names the columns: id, source_lat, source_lon, target_lat, target_lon and optionally vehicle, metric
+pub fn parse_csv<R: BufRead>(reader: R, config: &BatchConfig) -> io::Result<Vec<BatchRequest>> {
+	let mut lines = reader.lines();
+
+	let header: Vec<String> = match lines.next() {
+		Some(line) => {
+			let line = line?;
+			split_csv(&line).ok_or(invalid_row(1, &line))?
+		},
+		None => return Ok(Vec::new()),
+	};
+
+	let mut requests = Vec::new();
+	for (i, line) in lines.enumerate() {
+		let line = line?;
+		if line.trim().is_empty() {
+			continue;
+		}
+
+		let values = split_csv(&line).ok_or(invalid_row(i + 2, &line))?;
+		let row: HashMap<&str, &str> = header.iter().map(|column| column.as_str()).zip(values.iter().map(|value| value.as_str())).collect();
+		let request = build_request(i, config, |key| row.get(key).filter(|value| !value.is_empty()).map(|value| value.to_string()));
+		requests.push(request.ok_or(invalid_row(i + 2, &line))?);
+	}
+
+	Ok(requests)
+}
+
+// fields are separated by commas and may be quoted like the ids of csv_row, with quotes doubled.
+// unquoted fields are trimmed, None for unterminated quotes or text after a closing quote
+fn split_csv(line: &str) -> Option<Vec<String>> {
+	let mut fields = Vec::new();
+	let mut chars = line.chars().peekable();
+
+	loop {
+		let mut field = String::new();
+		while chars.peek() == Some(&' ') {
+			chars.next();
+		}
+
+		if chars.peek() == Some(&'"') {
+			chars.next();
+			loop {
+				match chars.next()? {
+					'"' if chars.peek() == Some(&'"') => {
+						chars.next();
+						field.push('"');
+					},
+					'"' => break,
+					c => field.push(c),
+				}
+			}
+			while chars.peek() == Some(&' ') {
+				chars.next();
+			}
+		} else {
+			while let Some(&c) = chars.peek() {
+				if c == ',' {
+					break;
+				}
+				field.push(c);
+				chars.next();
+			}
+			field = field.trim().to_string();
+		}
+
+		fields.push(field);
+		match chars.next() {
+			Some(',') => continue,
+			None => return Some(fields),
+			Some(_) => return None,
+		}
+	}
+}
+
+// same keys as the csv columns, coordinates may be numbers or strings
+pub fn parse_jsonl<R: BufRead>(reader: R, config: &BatchConfig) -> io::Result<Vec<BatchRequest>> {
+	let mut requests = Vec::new();
+
+	for (i, line) in reader.lines().enumerate() {
+		let line = line?;
+		if line.trim().is_empty() {
+			continue;
+		}
+
+		let object = match Json::from_str(&line) {
+			Ok(Json::Object(object)) => object,
+			_ => return Err(invalid_row(i + 1, &line)),
+		};
+
+		let request = build_request(i, config, |key| match object.get(key) {
+			Some(&Json::String(ref value)) => Some(value.clone()),
+			Some(value) if value.is_number() => Some(value.to_string()),
+			_ => None,
+		});
+		requests.push(request.ok_or(invalid_row(i + 1, &line))?);
+	}
+
+	Ok(requests)
+}
+
+// unknown vehicles and metrics make the row invalid, missing ones are taken from the config
+fn build_request<F>(index: usize, config: &BatchConfig, get: F) -> Option<BatchRequest>
+	where F: Fn(&str) -> Option<String> {
+	let coord = |key: &str| get(key).and_then(|value| value.parse::<f64>().ok());
+	let known = |key: &str, values: &[&str], default: &String| match get(key) {
+		Some(value) => if values.contains(&value.as_str()) { Some(value) } else { None },
+		None => Some(default.clone()),
+	};
+
+	Some(BatchRequest {
+		id: get("id").unwrap_or(index.to_string()),
+		source: ::data::Position { lat: coord("source_lat")?, lon: coord("source_lon")? },
+		target: ::data::Position { lat: coord("target_lat")?, lon: coord("target_lon")? },
+		vehicle: known("vehicle", &VEHICLES, &config.vehicle)?,
+		metric: known("metric", &METRICS, &config.metric)?,
+	})
+}
+
+fn invalid_row(line_nr: usize, line: &str) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, format!("invalid request in line {}: {}", line_nr, line))
+}
+
+// the workers take the next unrouted request from a shared counter, results are put back into input order
+pub fn route_all(data: Arc<::data::State>, requests: Vec<BatchRequest>, config: &BatchConfig) -> Vec<BatchResult> {
+	let requests = Arc::new(requests);
+	let next = Arc::new(AtomicUsize::new(0));
+	// tmc events are not available offline
+	let tmc_state = Arc::new(RwLock::new(::data::TMCState::new()));
+
+	let (sender, receiver) = mpsc::channel();
+
+	let mut handles = Vec::new();
+	for _ in 0..config.threads.max(1) {
+		let data = data.clone();
+		let requests = requests.clone();
+		let next = next.clone();
+		let tmc_state = tmc_state.clone();
+		let sender = sender.clone();
+		let geometry = config.geometry;
+
+		handles.push(thread::spawn(move || {
+			loop {
+				let i = next.fetch_add(1, Ordering::SeqCst);
+				if i >= requests.len() {
+					break;
+				}
+				sender.send((i, route_one(&data, &requests[i], geometry, &tmc_state))).unwrap();
+			}
+		}));
+	}
+	drop(sender);
+
+	let mut results: Vec<Option<BatchResult>> = vec![None; requests.len()];
+	for (i, result) in receiver {
+		results[i] = Some(result);
+	}
+
+	for handle in handles {
+		handle.join().unwrap();
+	}
+
+	results.into_iter().map(|result| result.unwrap()).collect()
+}
+
+fn route_one(data: &::data::State, request: &BatchRequest, geometry: bool, tmc_state: &RwLock<::data::TMCState>) -> BatchResult {
+	let constraints = ::routing::vehicle_constraints(&request.vehicle);
+	let query = ::data::SnapQuery::new(constraints);
+
+	let source_snap = data.find_closest_edge(&request.source, &query);
+	let target_snap = data.find_closest_edge(&request.target, &query);
+
+	let route = match (source_snap, target_snap) {
+		(Some(source), Some(target)) => ::routing::run_dijkstra_between(&data.routing_data, &::routing::Endpoint::Edge(source), &::routing::Endpoint::Edge(target), constraints, ::routing::cost_function(&request.metric, false), tmc_state),
+		_ => None,
+	};
+
+	match route {
+		Some(route) => BatchResult { id: request.id.clone(), distance: Some(route.distance), time: Some(route.time), path: if geometry { Some(route.path) } else { None } },
+		None => BatchResult { id: request.id.clone(), distance: None, time: None, path: None },
+	}
+}
+
+pub fn write_results(filename: &str, results: &[BatchResult]) -> io::Result<()> {
+	let mut writer = BufWriter::new(File::create(filename)?);
+
+	if is_json(filename) {
+		for result in results {
+			writeln!(writer, "{}", json::encode(result).unwrap())?;
+		}
+	} else {
+		writeln!(writer, "id,distance,time,geometry")?;
+		for result in results {
+			writeln!(writer, "{}", csv_row(result))?;
+		}
+	}
+
+	writer.flush()
+}
+
+// missing routes leave distance and time empty, the geometry is written as quoted wkt linestring.
+// ids are quoted as well, quotes within them are doubled
+fn csv_row(result: &BatchResult) -> String {
+	let distance = result.distance.map(|d| format!("{:.1}", d)).unwrap_or(String::new());
+	let time = result.time.map(|t| format!("{:.1}", t)).unwrap_or(String::new());
+	let geometry = match result.path {
+		Some(ref path) => format!("\"LINESTRING({})\"", path.iter().map(|p| format!("{} {}", p[1], p[0])).collect::<Vec<String>>().join(", ")),
+		None => String::new(),
+	};
+
+	format!("\"{}\",{},{},{}", result.id.replace('"', "\"\""), distance, time, geometry)
+}
+
+#[test]
+fn test_parse_requests() {
+	let config = BatchConfig { threads: 1, geometry: false, vehicle: "car".to_string(), metric: "time".to_string() };
+
+	let csv = "id,source_lat,source_lon,target_lat,target_lon,vehicle\na,48.1,9.1,48.2,9.2,bike\n\nb,48.3,9.3,48.4,9.4,\n";
+	let requests = parse_csv(io::Cursor::new(csv), &config).unwrap();
+	assert_eq!(requests.len(), 2);
+	assert_eq!(requests[0].id, "a");
+	assert_eq!(requests[0].vehicle, "bike");
+	assert_eq!(requests[1].target.lon, 9.4);
+	assert_eq!(requests[1].vehicle, "car");
+
+	let jsonl = "{\"source_lat\": 48.1, \"source_lon\": 9.1, \"target_lat\": \"48.2\", \"target_lon\": 9.2, \"metric\": \"distance\"}\n";
+	let requests = parse_jsonl(io::Cursor::new(jsonl), &config).unwrap();
+	assert_eq!(requests[0].id, "0");
+	assert_eq!(requests[0].target.lat, 48.2);
+	assert_eq!(requests[0].metric, "distance");
+	assert_eq!(requests[0].vehicle, "car");
+
+	assert!(parse_csv(io::Cursor::new("source_lat,source_lon\n1,2\n"), &config).is_err());
+
+	// unknown vehicles and metrics are invalid rows
+	assert!(parse_csv(io::Cursor::new("id,source_lat,source_lon,target_lat,target_lon,vehicle\na,48.1,9.1,48.2,9.2,truck\n"), &config).is_err());
+	assert!(parse_jsonl(io::Cursor::new("{\"source_lat\": 48.1, \"source_lon\": 9.1, \"target_lat\": 48.2, \"target_lon\": 9.2, \"metric\": \"fastest\"}\n"), &config).is_err());
+
+	// ids may contain commas and quotes
+	let result = BatchResult { id: "a, \"b\"".to_string(), distance: Some(1000.0), time: None, path: Some(vec![[48.1, 9.1], [48.2, 9.2]]) };
+	assert_eq!(csv_row(&result), "\"a, \"\"b\"\"\",1000.0,,\"LINESTRING(9.1 48.1, 9.2 48.2)\"");
+
+	// quoted ids are read back the way they are written
+	let csv = format!("id,source_lat,source_lon,target_lat,target_lon\n{},48.1,9.1,48.2,9.2\n", csv_row(&result).split(",1000").next().unwrap());
+	assert_eq!(parse_csv(io::Cursor::new(csv), &config).unwrap()[0].id, "a, \"b\"");
+	assert_eq!(split_csv(" \"x,y\" , 2,"), Some(vec!["x,y".to_string(), "2".to_string(), String::new()]));
+	assert_eq!((split_csv("\"x"), split_csv("\"x\"y,1")), (None, None));
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+
+pub const USAGE: &'static str = "usage:
+  rust_fapraosm import <pbf> [-o <state>] [--lcl <dir>]
+  rust_fapraosm serve [--state <state>] [--pbf <pbf>] [--lcl <dir>] [--bind <addr>] [--port <port>] [--web-root <dir>] [--tmc-source <source>] [--tmc-record <file>] [--replay-speed <factor>] [--tmc-events <dir>] [--tmc-language <lang>]
+  rust_fapraosm route <lat,lon> <lat,lon> [--state <state>] [--vehicle <car|bike|walk>] [--metric <time|distance>] [--format <json|geojson|gpx|polyline5|polyline6>] [--simplify <meters>] [--tmc <file>] [--tmc-events <dir>]
+  rust_fapraosm batch <input> -o <output> [--state <state>] [--threads <n>] [--geometry <true|false>] [--vehicle <car|bike|walk>] [--metric <time|distance>]
+  rust_fapraosm info <state>
+
+state files ending with .gz are written zlib compressed, all others in the memory mapped flat format.
+--lcl reads the tmc location code list (POINTS, POFFSETS, ROADS, SEGMENTS) from the directory while building the state.
+tmc sources: rdsd (default), sample, file:<path>, replay:<path>, tcp:<host:port>, udp:<host:port>, stdin, rds:<source> (raw hex groups) or none.
+--tmc-record appends every received line with its time to a recording, which replay:<path> plays back at
+--replay-speed times the original speed (0: all at once). route --tmc applies all events of a log or recording.
+--tmc-events reads the event list (events.tsv, event_classes.tsv) from the directory (default: rds), --tmc-language
+selects the language of the event descriptions (default: en).";
+
+pub const DEFAULT_STATE_FILE: &'static str = "state.bin";
+
+pub enum Command {
+	// lcl is a directory with the tmc location code list
+	Import { pbf: OsString, output: String, lcl: Option<String> },
+	// the state is (re)built from pbf if it is missing or outdated
+	Serve { state: String, pbf: Option<OsString>, lcl: Option<String>, config: ::server::ServerConfig },
+	Route { state: String, source: String, target: String, vehicle: String, metric: String, format: ::format::RouteFormat, simplify: Option<f64>, tmc: Option<String>, tmc_events: String },
+	// vehicle and metric are the defaults for rows without these columns
+	Batch { state: String, input: String, output: String, config: ::batch::BatchConfig },
+	Info { state: String },
+	Help,
+}
+
+struct Arguments {
+	positional: Vec<String>,
+	options: HashMap<String, String>,
+}
+
+impl Arguments {
+	fn option(&self, name: &str, default: &str) -> String {
+		self.options.get(name).cloned().unwrap_or(default.to_string())
+	}
+}
+
+pub fn parse_args(args: &[String]) -> Result<Command, String> {
+	let (command, rest) = match args.split_first() {
+		Some((command, rest)) => (command.as_str(), rest),
+		None => return Ok(Command::Help),
+	};
+
+	match command {
+		"import" => {
+			let args = split_args(rest, &["-o", "--lcl"], 1)?;
+			Ok(Command::Import { pbf: OsString::from(args.positional[0].clone()), output: args.option("-o", DEFAULT_STATE_FILE), lcl: args.options.get("--lcl").cloned() })
+		},
+		"serve" => {
+			let args = split_args(rest, &["--state", "--pbf", "--lcl", "--bind", "--port", "--web-root", "--tmc-source", "--tmc-record", "--replay-speed", "--tmc-events", "--tmc-language"], 0)?;
+
+			let port = args.option("--port", "8080");
+			let port = port.parse::<u16>().map_err(|_| format!("invalid port: {}", port))?;
+
+			let mut tmc_source = ::tmc_source::SourceConfig::parse(&args.option("--tmc-source", "rdsd"))?;
+			if let Some(factor) = args.options.get("--replay-speed") {
+				match tmc_source {
+					::tmc_source::SourceConfig::Replay { ref mut speed, .. } => *speed = factor.parse::<f64>().ok().filter(|speed| *speed >= 0.0).ok_or(format!("invalid replay speed: {}", factor))?,
+					_ => return Err("--replay-speed needs a replay:<path> tmc source".to_string()),
+				}
+			}
+
+			let config = ::server::ServerConfig { bind: args.option("--bind", "127.0.0.1"), port: port, web_root: args.option("--web-root", "web/"), tmc_source: tmc_source, tmc_record: args.options.get("--tmc-record").cloned(), tmc_events: args.option("--tmc-events", ::events::DEFAULT_DIR), tmc_language: args.option("--tmc-language", ::events::DEFAULT_LANGUAGE) };
+			Ok(Command::Serve { state: args.option("--state", DEFAULT_STATE_FILE), pbf: args.options.get("--pbf").map(OsString::from), lcl: args.options.get("--lcl").cloned(), config: config })
+		},
+		"route" => {
+			let args = split_args(rest, &["--state", "--vehicle", "--metric", "--format", "--simplify", "--tmc", "--tmc-events"], 2)?;
+
+			let format = args.option("--format", "json");
+			let format = ::format::RouteFormat::parse(&format).ok_or(format!("unknown format: {}", format))?;
+
+			let simplify = match args.options.get("--simplify") {
+				Some(tolerance) => Some(tolerance.parse::<f64>().map_err(|_| format!("invalid tolerance: {}", tolerance))?),
+				None => None,
+			};
+
+			Ok(Command::Route { state: args.option("--state", DEFAULT_STATE_FILE), source: args.positional[0].clone(), target: args.positional[1].clone(), vehicle: args.option("--vehicle", "car"), metric: args.option("--metric", "time"), format: format, simplify: simplify, tmc: args.options.get("--tmc").cloned(), tmc_events: args.option("--tmc-events", ::events::DEFAULT_DIR) })
+		},
+		"batch" => {
+			let args = split_args(rest, &["-o", "--state", "--threads", "--geometry", "--vehicle", "--metric"], 1)?;
+
+			let threads = args.option("--threads", "4");
+			let threads = threads.parse::<usize>().map_err(|_| format!("invalid thread count: {}", threads))?;
+			let geometry = args.option("--geometry", "false");
+			let geometry = geometry.parse::<bool>().map_err(|_| format!("invalid geometry flag: {}", geometry))?;
+
+			let output = args.options.get("-o").cloned().ok_or("missing output file (-o)".to_string())?;
+			let config = ::batch::BatchConfig { threads: threads, geometry: geometry, vehicle: args.option("--vehicle", "car"), metric: args.option("--metric", "time") };
+			Ok(Command::Batch { state: args.option("--state", DEFAULT_STATE_FILE), input: args.positional[0].clone(), output: output, config: config })
+		},
+		"info" => {
+			let args = split_args(rest, &[], 1)?;
+			Ok(Command::Info { state: args.positional[0].clone() })
+		},
+		"help" | "-h" | "--help" => Ok(Command::Help),
+		_ => Err(format!("unknown command: {}", command)),
+	}
+}
+
+// every option takes exactly one value
+fn split_args(args: &[String], allowed: &[&str], positional_cnt: usize) -> Result<Arguments, String> {
+	let mut result = Arguments { positional: Vec::new(), options: HashMap::new() };
+	let mut iter = args.iter();
+
+	while let Some(arg) = iter.next() {
+		if arg.starts_with("-") && arg.len() > 1 {
+			if !allowed.contains(&arg.as_str()) {
+				return Err(format!("unknown option: {}", arg));
+			}
+			match iter.next() {
+				Some(value) => result.options.insert(arg.clone(), value.clone()),
+				None => return Err(format!("missing value for option: {}", arg)),
+			};
+		} else {
+			result.positional.push(arg.clone());
+		}
+	}
+
+	if result.positional.len() != positional_cnt {
+		return Err(format!("expected {} arguments, got {}", positional_cnt, result.positional.len()));
+	}
+
+	Ok(result)
+}
+
+#[test]
+fn test_parse_args() {
+	let args: Vec<String> = vec!["serve", "--port", "9000", "--state", "bw.bin.gz"].iter().map(|s| s.to_string()).collect();
+
+	match parse_args(&args) {
+		Ok(Command::Serve { state, pbf, lcl, config }) => {
+			assert_eq!(state, "bw.bin.gz");
+			assert!(pbf.is_none() && lcl.is_none());
+			assert_eq!(config.port, 9000);
+			assert_eq!(config.bind, "127.0.0.1");
+			assert_eq!(config.tmc_source, ::tmc_source::SourceConfig::Rdsd);
+			assert_eq!((config.tmc_events.as_str(), config.tmc_language.as_str()), ("rds", "en"));
+		},
+		_ => panic!("expected serve command"),
+	}
+
+	let args: Vec<String> = vec!["serve", "--tmc-source", "replay:rush_hour.log", "--replay-speed", "10"].iter().map(|s| s.to_string()).collect();
+	match parse_args(&args) {
+		Ok(Command::Serve { config, .. }) => assert_eq!(config.tmc_source, ::tmc_source::SourceConfig::Replay { path: "rush_hour.log".to_string(), speed: 10.0 }),
+		_ => panic!("expected serve command"),
+	}
+
+	let args: Vec<String> = vec!["serve", "--tmc-language", "de"].iter().map(|s| s.to_string()).collect();
+	match parse_args(&args) {
+		Ok(Command::Serve { config, .. }) => assert_eq!(config.tmc_language, "de"),
+		_ => panic!("expected serve command"),
+	}
+
+	let args: Vec<String> = vec!["serve", "--tmc-source", "sample", "--replay-speed", "10"].iter().map(|s| s.to_string()).collect();
+	assert!(parse_args(&args).is_err());
+
+	let args: Vec<String> = vec!["import", "bw.osm.pbf", "--lcl", "LCL16.0.D"].iter().map(|s| s.to_string()).collect();
+	match parse_args(&args) {
+		Ok(Command::Import { lcl, .. }) => assert_eq!(lcl, Some("LCL16.0.D".to_string())),
+		_ => panic!("expected import command"),
+	}
+
+	let args: Vec<String> = vec!["route", "48.1,9.1", "--vehicle"].iter().map(|s| s.to_string()).collect();
+	assert!(parse_args(&args).is_err());
+}
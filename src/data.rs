@@ -1,9 +1,17 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
-use std::cmp;
 use std::f64;
+use std::fmt;
+use std::io;
+use std::io::Write;
+use std::ffi::OsString;
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
 
-#[derive(Debug, RustcEncodable, RustcDecodable)]
+use flat::{FlatVec, FlatItem, FlatWriter, FlatReader, write_raw};
+
+#[derive(Debug, Clone, Copy, RustcEncodable, RustcDecodable)]
 pub struct Position {
 	pub lat: f64,
 	pub lon: f64,
@@ -25,25 +33,175 @@ impl Position {
 
 		dist
 	}
+
+	// initial bearing towards other in degrees, clockwise from north
+	pub fn bearing(&self, other: &Position) -> f64 {
+		let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+		let d_lng = (other.lon - self.lon).to_radians();
+
+		let y = d_lng.sin() * lat2.cos();
+		let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * d_lng.cos();
+
+		(y.atan2(x).to_degrees() + 360.0) % 360.0
+	}
+
+	// projects onto the segment a-b in a local equirectangular approximation, returns the ratio along the segment and the projected position
+	pub fn project_onto(&self, a: &Position, b: &Position) -> (f64, Position) {
+		let scale = self.lat.to_radians().cos();
+		let (dx, dy) = ((b.lon - a.lon) * scale, b.lat - a.lat);
+		let (px, py) = ((self.lon - a.lon) * scale, self.lat - a.lat);
+
+		let len2 = dx * dx + dy * dy;
+		let ratio = if len2 > 0.0 { ((px * dx + py * dy) / len2).max(0.0).min(1.0) } else { 0.0 };
+
+		(ratio, Position { lat: a.lat + ratio * (b.lat - a.lat), lon: a.lon + ratio * (b.lon - a.lon) })
+	}
+}
+
+#[test]
+fn test_project_onto() {
+	let a = Position { lat: 48.0, lon: 9.0 };
+	let b = Position { lat: 48.0, lon: 9.1 };
+
+	let (ratio, projected) = Position { lat: 48.01, lon: 9.025 }.project_onto(&a, &b);
+	assert!((ratio - 0.25).abs() < 1e-9);
+	assert!((projected.lat - 48.0).abs() < 1e-9);
+
+	// positions behind the segment end are clamped to it
+	let (ratio, _) = Position { lat: 48.0, lon: 9.2 }.project_onto(&a, &b);
+	assert_eq!(ratio, 1.0);
+
+	assert!((a.bearing(&b) - 90.0).abs() < 0.1);
+
+	let query = SnapQuery { constraints: FLAG_CAR, radius: None, bearing: Some((350.0, 45.0)) };
+	assert!(query.accepts_bearing(20.0));
+	assert!(!query.accepts_bearing(90.0));
+}
+
+// a position projected onto an edge, ratio 0 is the source and 1 the target of the edge
+#[derive(Debug, Clone, Copy)]
+pub struct Snap {
+	pub edge: usize,
+	pub ratio: f64,
+	pub position: Position,
+	// meters between the query position and the snapped position
+	pub distance: f64,
+	// the edge may only be used in its own direction, set if a bearing was requested
+	pub directed: bool,
+}
+
+// restricts which edges a position may be snapped onto
+#[derive(Debug, Clone, Copy)]
+pub struct SnapQuery {
+	// profile flags of which at least one has to be allowed on the edge
+	pub constraints: u8,
+	// meters
+	pub radius: Option<f64>,
+	// degrees clockwise from north and the allowed deviation
+	pub bearing: Option<(f64, f64)>,
+}
+
+impl SnapQuery {
+	pub fn new(constraints: u8) -> SnapQuery {
+		SnapQuery { constraints: constraints, radius: None, bearing: None }
+	}
+
+	fn accepts_bearing(&self, bearing: f64) -> bool {
+		match self.bearing {
+			Some((expected, range)) => {
+				let diff = (bearing - expected).abs() % 360.0;
+				diff.min(360.0 - diff) <= range
+			},
+			None => true,
+		}
+	}
 }
 
+// fixed point scale of node coordinates, 1e-7 degrees fit into an i32
+pub const COORD_SCALE: f64 = 10000000.0;
+
 pub const FLAG_CAR: u8 = 0b00000001;
 pub const FLAG_BIKE: u8 = 0b00000010;
 pub const FLAG_WALK: u8 = 0b00000100;
 
-#[derive(Debug, RustcEncodable, RustcDecodable)]
+pub const PROFILES: [u8; 3] = [FLAG_CAR, FLAG_BIKE, FLAG_WALK];
+
+// reduction of the travel speed per profile in the order of PROFILES, 1.0 closes the edge for the profile
+pub type Slowdown = [f64; 3];
+
+pub fn profile_index(profile: u8) -> usize {
+	PROFILES.iter().position(|p| *p == profile).unwrap_or(0)
+}
+
+// road classes of the edges' highway tags, links belong to the class of their road
+pub const CLASS_MOTORWAY: u8 = 1;
+pub const CLASS_TRUNK: u8 = 2;
+pub const CLASS_PRIMARY: u8 = 3;
+pub const CLASS_SECONDARY: u8 = 4;
+pub const CLASS_TERTIARY: u8 = 5;
+pub const CLASS_OTHER: u8 = 6;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, RustcEncodable, RustcDecodable)]
 pub struct RoutingEdge {
-	pub source: usize,
-	pub target: usize,
-	pub length: f64,
-	pub speed: f64,
+	pub source: u32,
+	pub target: u32,
+	pub length: f32,
+	pub speed: f32,
 	pub constraints: u8,
 }
 
-#[derive(Debug, RustcEncodable, RustcDecodable)]
-pub struct OsmNode {
-	pub position: Position,
-	pub internal_id: usize
+#[repr(C)]
+#[derive(Debug, Clone, Copy, RustcEncodable, RustcDecodable)]
+pub struct RoutingNode {
+	pub lat: i32,
+	pub lon: i32,
+}
+
+impl RoutingNode {
+	pub fn from_position(position: &Position) -> RoutingNode {
+		RoutingNode { lat: (position.lat * COORD_SCALE).round() as i32, lon: (position.lon * COORD_SCALE).round() as i32 }
+	}
+
+	pub fn position(&self) -> Position {
+		Position { lat: self.lat as f64 / COORD_SCALE, lon: self.lon as f64 / COORD_SCALE }
+	}
+}
+
+unsafe impl FlatItem for RoutingNode {
+	fn write_flat<W: Write>(&self, w: &mut W) -> io::Result<()> {
+		write_raw(&self.lat, w)?;
+		write_raw(&self.lon, w)
+	}
+}
+
+unsafe impl FlatItem for RoutingEdge {
+	fn write_flat<W: Write>(&self, w: &mut W) -> io::Result<()> {
+		write_raw(&self.source, w)?;
+		write_raw(&self.target, w)?;
+		write_raw(&self.length, w)?;
+		write_raw(&self.speed, w)?;
+		write_raw(&self.constraints, w)?;
+		// repr(C) padding
+		w.write_all(&[0; 3])
+	}
+}
+
+// house number of an address node or building, strings are ids into State::names
+#[repr(C)]
+#[derive(Debug, Clone, Copy, RustcEncodable, RustcDecodable)]
+pub struct Address {
+	pub position: RoutingNode,
+	pub housenumber: u32,
+	pub street: u32,
+}
+
+unsafe impl FlatItem for Address {
+	fn write_flat<W: Write>(&self, w: &mut W) -> io::Result<()> {
+		self.position.write_flat(w)?;
+		write_raw(&self.housenumber, w)?;
+		write_raw(&self.street, w)
+	}
 }
 
 #[derive(Debug, RustcEncodable, RustcDecodable, Hash, Eq, PartialEq, Clone)]
@@ -53,120 +211,501 @@ pub struct TMCTag {
 	pub next: u32
 }
 
+// a location table, identified by the country id (CID) and table number (TABCD, called LTN in rds) of its location code list
+#[derive(Debug, RustcEncodable, RustcDecodable, Hash, Eq, PartialEq, Ord, PartialOrd, Clone, Copy)]
+pub struct TMCTable {
+	pub cid: u32,
+	pub tabcd: u32,
+}
+
+// the german location code list, used for tags without country and until the receiver reports its table
+pub const DEFAULT_TMC_TABLE: TMCTable = TMCTable { cid: 58, tabcd: 1 };
+
+// iso country code, rds country code (first digit of the pi code) and the country id of the location code list.
+// rds country codes are shared by several countries, without the extended country code the first listed one is assumed
+const TMC_COUNTRIES: [(&'static str, u8, u32); 6] = [
+	("DE", 0xd, 58),
+	("AT", 0xa, 1),
+	("CH", 0x4, 9),
+	("NL", 0x8, 40),
+	("BE", 0x6, 6),
+	("FR", 0xf, 17),
+];
+
+impl TMCTable {
+	// tabcd 1 is the main table of a country
+	pub fn from_iso(code: &str) -> Option<TMCTable> {
+		TMC_COUNTRIES.iter().find(|&&(iso, _, _)| iso.eq_ignore_ascii_case(code)).map(|&(_, _, cid)| TMCTable { cid: cid, tabcd: 1 })
+	}
+
+	pub fn from_rds(country: u8, ltn: u8) -> Option<TMCTable> {
+		TMC_COUNTRIES.iter().find(|&&(_, rds, _)| rds == country).map(|&(_, _, cid)| TMCTable { cid: cid, tabcd: ltn as u32 })
+	}
+}
+
+// location codes are only unique within their table
+#[derive(Debug, RustcEncodable, RustcDecodable, Hash, Eq, PartialEq, Ord, PartialOrd, Clone, Copy)]
+pub struct TMCLocation {
+	pub table: TMCTable,
+	pub loc: u32,
+}
+
+impl TMCLocation {
+	pub fn new(table: TMCTable, loc: u32) -> TMCLocation {
+		TMCLocation { table: table, loc: loc }
+	}
+}
+
+// cid:tabcd:loc, used by the api and the tiles
+impl fmt::Display for TMCLocation {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}:{}:{}", self.table.cid, self.table.tabcd, self.loc)
+	}
+}
+
 #[derive(Debug, RustcEncodable, RustcDecodable, Hash, Eq, PartialEq, Clone)]
 pub struct TMCKey {
-	pub loc: u32,
+	pub loc: TMCLocation,
 	pub dir: bool,
 	pub event: u32,
 }
 
-#[derive(Debug)]
+// a complete ALERT-C message, the optional content is only known once all groups of a multi-group message arrived
+#[derive(Debug, Clone)]
 pub struct TMCRawEvent {
+	// the table which was active when the message was received
+	pub table: TMCTable,
 	pub loc: u32,
 	pub dir: bool,
 	pub event: u32,
-	pub ext: u32
+	pub ext: u32,
+	// duration and persistence code
+	pub duration: u32,
+	// control codes: the event affects both directions, its duration is read as the other type (dynamic/longer-lasting)
+	pub both_directions: bool,
+	pub duration_type_changed: bool,
+	pub diversion: bool,
+	// raw value, its meaning depends on the quantifier type of the event
+	pub quantifier: Option<u32>,
+	// advised speed limit in km/h
+	pub speed_limit: Option<u32>,
+	// length of the affected route in km
+	pub length: Option<u32>,
+	// explicit start and stop time codes
+	pub start_time: Option<u32>,
+	pub stop_time: Option<u32>,
+	pub additional_events: Vec<u32>,
+	pub supplementary: Vec<u32>,
+}
+
+impl TMCRawEvent {
+	pub fn new(loc: u32, dir: bool, event: u32, ext: u32) -> TMCRawEvent {
+		TMCRawEvent { table: DEFAULT_TMC_TABLE, loc: loc, dir: dir, event: event, ext: ext, duration: 0, both_directions: false, duration_type_changed: false, diversion: false, quantifier: None, speed_limit: None, length: None, start_time: None, stop_time: None, additional_events: Vec::new(), supplementary: Vec::new() }
+	}
+
+	pub fn location(&self) -> TMCLocation {
+		TMCLocation::new(self.table, self.loc)
+	}
 }
 
 // updated during runtime by tmc thread
 #[derive(Debug)]
 pub struct TMCState {
-	pub current_edge_events: HashMap<usize, f64>,
-	pub current_tmc_events: HashMap<TMCKey, TMCEvent>
+	pub current_edge_events: HashMap<usize, Slowdown>,
+	pub current_tmc_events: HashMap<TMCKey, TMCEvent>,
+	// (loc, dir, ext) -> edges of the path between the location points, None if there is none
+	pub extent_cache: HashMap<(TMCLocation, bool, u32), Option<Vec<usize>>>
+}
+
+impl TMCState {
+	pub fn new() -> TMCState {
+		TMCState { current_edge_events: HashMap::new(), current_tmc_events: HashMap::new(), extent_cache: HashMap::new() }
+	}
+
+	pub fn slowdown(&self, edge: usize, profile: u8) -> f64 {
+		self.current_edge_events.get(&edge).map(|slowdown| slowdown[profile_index(profile)]).unwrap_or(0.0)
+	}
 }
 
 #[derive(Debug)]
 pub struct TMCEvent {
 	pub desc: String,
-	pub slowdown: f64,
+	pub slowdown: Slowdown,
 	pub ext: u32,
-	pub timeout: u32,
-	pub edges: HashSet<usize>
+	// unix time in seconds, refreshed whenever the message is broadcast again
+	pub received: i64,
+	pub expires: i64,
+	// explicit start time of the message, if given
+	pub starts: Option<i64>,
+	// affected edges and their slowdown
+	pub edges: HashMap<usize, Slowdown>,
+	pub message: TMCRawEvent
+}
+
+// a location point of the tmc location code list
+#[derive(Debug, Clone, RustcEncodable, RustcDecodable)]
+pub struct TMCPoint {
+	pub position: Position,
+	// location code of the road the point lies on
+	pub road: u32,
+	// e.g. "A 8", empty if unknown
+	pub road_number: String,
 }
 
 #[derive(Debug, RustcEncodable, RustcDecodable)]
 pub struct RoutingData {
-	// relevant nodes and their position
-	pub osm_nodes: HashMap<i64, OsmNode>,
-	//[n_id] -> osm_n_id
-	pub internal_nodes: Vec<i64>,
+	//[n_id] -> osm_n_id, only needed for debugging and export
+	pub internal_nodes: FlatVec<i64>,
+	//[n_id] -> fixed point position
+	pub node_positions: FlatVec<RoutingNode>,
 	// [e_id] -> target_n_id|length|constraints
-	pub internal_edges: Vec<RoutingEdge>,
+	pub internal_edges: FlatVec<RoutingEdge>,
 	// [n_id] -> e_id
-	pub internal_offset: Vec<usize>,
+	pub internal_offset: FlatVec<u32>,
 	// tmc_loc -> set<internal_edge_id>
-	pub tmc_mapping: HashMap<u32, HashSet<usize>>,
+	pub tmc_mapping: HashMap<TMCLocation, HashSet<usize>>,
 	// tmc_loc -> tmc_loc
-	pub tmc_next: HashMap<(u32, bool), u32>,
+	pub tmc_next: HashMap<(TMCLocation, bool), TMCLocation>,
+	// tmc_loc -> point of the location table, empty if the state was built without one
+	pub tmc_points: HashMap<TMCLocation, TMCPoint>,
+	// [n_id] -> profile flags whose largest strongly connected component contains the node
+	pub node_components: FlatVec<u8>,
+	// [e_id] -> string id of the way's name and ref tags
+	pub edge_names: FlatVec<u32>,
+	pub edge_refs: FlatVec<u32>,
+	// [e_id] -> road class
+	pub edge_classes: FlatVec<u8>,
 }
 
-#[derive(Debug, RustcEncodable, RustcDecodable)]
-pub struct State {
-	pub routing_data: RoutingData,
-	pub grid: Grid,
+impl RoutingData {
+	pub fn position(&self, node: usize) -> Position {
+		self.node_positions[node].position()
+	}
+
+	pub fn edge_range(&self, node: usize) -> (usize, usize) {
+		let start = self.internal_offset[node] as usize;
+		let next_node = node + 1;
+
+		if next_node > self.internal_offset.len() - 1 {
+			let max_end = self.internal_edges.len();
+			assert!(start <= max_end, "invalid offset lookup max!");
+
+			return (start, max_end);
+		}
+
+		let end = self.internal_offset[next_node] as usize;
+
+		assert!(start <= end, "invalid offset lookup!");
+
+		return (start, end);
+	}
+
+	pub fn project_onto_edge(&self, edge: usize, position: &Position) -> (f64, Position) {
+		let edge = &self.internal_edges[edge];
+		position.project_onto(&self.position(edge.source as usize), &self.position(edge.target as usize))
+	}
+
+	// [e_id] -> tmc location codes, the reverse of tmc_mapping
+	pub fn edge_tmc_locations(&self) -> HashMap<usize, Vec<TMCLocation>> {
+		let mut result: HashMap<usize, Vec<TMCLocation>> = HashMap::new();
+
+		for (location, edges) in &self.tmc_mapping {
+			for edge in edges {
+				result.entry(*edge).or_insert(Vec::new()).push(*location);
+			}
+		}
+		for locations in result.values_mut() {
+			locations.sort();
+			locations.dedup();
+		}
+
+		result
+	}
+
+	// the edge running in opposite direction between the same nodes, if the way isn't oneway
+	pub fn reverse_edge(&self, edge: usize) -> Option<usize> {
+		let forward = &self.internal_edges[edge];
+		let (start, end) = self.edge_range(forward.target as usize);
+
+		(start..end).find(|&candidate| self.internal_edges[candidate].target == forward.source)
+	}
+}
+
+// non-decreasing and not larger than max
+pub fn is_monotonic(offsets: &[u32], max: usize) -> bool {
+	offsets.windows(2).all(|pair| pair[0] <= pair[1]) && offsets.last().map(|last| *last as usize <= max).unwrap_or(true)
 }
 
+// has to be increased whenever the layout of State changes
+pub const STATE_VERSION: u32 = 6;
+
+// stored in front of the state data, describes how and from what the state was built
 #[derive(Debug, Clone, RustcEncodable, RustcDecodable)]
-pub struct Bin {
-	pub nodes: Vec<i64>
+pub struct StateHeader {
+	pub version: u32,
+	// file name, size and modification time (unix seconds) of the parsed pbf
+	pub source_name: String,
+	pub source_size: u64,
+	pub source_modified: u64,
+	// hash of the way filter lists used during the parse
+	pub profile_hash: u64,
+	// unix seconds
+	pub build_time: i64,
+}
+
+impl StateHeader {
+	// source information is left empty if the source is unknown or missing
+	pub fn new(source: &Option<OsString>, profile_hash: u64) -> StateHeader {
+		let mut header = StateHeader { version: STATE_VERSION, source_name: String::new(), source_size: 0, source_modified: 0, profile_hash: profile_hash, build_time: 0 };
+
+		if let Some(ref filename) = *source {
+			let path = Path::new(filename);
+			if let Ok(metadata) = fs::metadata(path) {
+				header.source_name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or(String::new());
+				header.source_size = metadata.len();
+				header.source_modified = metadata.modified().ok().and_then(|modified| modified.duration_since(UNIX_EPOCH).ok()).map(|d| d.as_secs()).unwrap_or(0);
+			}
+		}
+
+		header
+	}
+
+	pub fn check(&self, expected: &StateHeader) -> Result<(), String> {
+		self.check_version(expected)?;
+		self.check_source(expected)
+	}
+
+	// states of another layout or profile can't be used at all, whatever they were built from
+	pub fn check_version(&self, expected: &StateHeader) -> Result<(), String> {
+		if self.version != expected.version {
+			return Err(format!("state has format version {}, expected {}", self.version, expected.version));
+		}
+		if self.profile_hash != expected.profile_hash {
+			return Err(format!("state was built with profile {:016x}, expected {:016x}", self.profile_hash, expected.profile_hash));
+		}
+		Ok(())
+	}
+
+	// unknown sources are not compared
+	pub fn check_source(&self, expected: &StateHeader) -> Result<(), String> {
+		if !expected.source_name.is_empty() && (self.source_name != expected.source_name || self.source_size != expected.source_size || self.source_modified != expected.source_modified) {
+			return Err(format!("state was built from {} ({} bytes, modified {}), expected {} ({} bytes, modified {})",
+				self.source_name, self.source_size, self.source_modified, expected.source_name, expected.source_size, expected.source_modified));
+		}
+		Ok(())
+	}
+}
+
+#[test]
+fn test_state_header_check() {
+	let expected = StateHeader { version: STATE_VERSION, source_name: "a.osm.pbf".to_string(), source_size: 10, source_modified: 20, profile_hash: 30, build_time: 0 };
+
+	let mut header = expected.clone();
+	header.build_time = 40;
+	assert!(header.check(&expected).is_ok());
+
+	header.source_size = 11;
+	assert!(header.check(&expected).is_err());
+
+	// unknown sources are not compared
+	let unknown_source = StateHeader { source_name: String::new(), ..expected.clone() };
+	assert!(header.check(&unknown_source).is_ok());
+
+	header.version = STATE_VERSION + 1;
+	assert!(header.check(&unknown_source).is_err());
+	assert!(header.check_version(&unknown_source).is_err());
+
+	// the version and profile are checked even without a source
+	header.version = STATE_VERSION;
+	header.profile_hash = 31;
+	assert!(header.check_source(&unknown_source).is_ok());
+	assert!(header.check(&unknown_source).is_err());
 }
 
 #[derive(Debug, RustcEncodable, RustcDecodable)]
-pub struct Grid {
-	pub bbox: BoundingBox,
-	pub bin_count_lat: usize,
-	pub bin_count_lon: usize,
-	pub bins: Vec<Bin>,
+pub struct State {
+	pub routing_data: RoutingData,
+	// item ids are edge ids
+	pub edge_index: ::rtree::RTree,
+	// road names, refs and address parts
+	pub names: ::strings::StringTable,
+	pub addresses: FlatVec<Address>,
+	// item ids are address ids
+	pub address_index: ::rtree::RTree,
 }
 
-impl Grid {
-	pub fn calc_bin_position(&self, lat_bin: usize, lon_bin: usize) -> usize {
-		assert!(lat_bin < self.bin_count_lat && lon_bin < self.bin_count_lon, "invalid lat or lon bin given!");
-		lat_bin * self.bin_count_lon + lon_bin
+impl State {
+	// the section order has to match read_flat
+	pub fn write_flat<W: Write>(&self, w: &mut FlatWriter<W>) -> io::Result<()> {
+		w.write_section(&self.routing_data.internal_nodes)?;
+		w.write_section(&self.routing_data.node_positions)?;
+		w.write_section(&self.routing_data.internal_edges)?;
+		w.write_section(&self.routing_data.internal_offset)?;
+		w.write_section(&self.routing_data.node_components)?;
+		w.write_encoded(&self.routing_data.tmc_mapping)?;
+		w.write_encoded(&self.routing_data.tmc_next)?;
+		w.write_encoded(&self.routing_data.tmc_points)?;
+		w.write_section(&self.edge_index.boxes)?;
+		w.write_section(&self.edge_index.level_offsets)?;
+		w.write_section(&self.edge_index.items)?;
+		w.write_section(&self.routing_data.edge_names)?;
+		w.write_section(&self.routing_data.edge_refs)?;
+		w.write_section(&self.routing_data.edge_classes)?;
+		w.write_section(&self.names.bytes)?;
+		w.write_section(&self.names.offsets)?;
+		w.write_section(&self.addresses)?;
+		w.write_section(&self.address_index.boxes)?;
+		w.write_section(&self.address_index.level_offsets)?;
+		w.write_section(&self.address_index.items)
 	}
 
-	pub fn calc_bin_index(&self, position: &Position) -> (usize, usize) {
-		let lat_bin = ((position.lat - self.bbox.min_lat) * self.bin_count_lat as f64) / (self.bbox.max_lat - self.bbox.min_lat);
-		let lon_bin = ((position.lon - self.bbox.min_lon) * self.bin_count_lon as f64) / (self.bbox.max_lat - self.bbox.min_lon);
+	pub fn read_flat(r: &mut FlatReader) -> io::Result<State> {
+		let mut routing_data = RoutingData {
+			internal_nodes: r.read_section()?,
+			node_positions: r.read_section()?,
+			internal_edges: r.read_section()?,
+			internal_offset: r.read_section()?,
+			node_components: r.read_section()?,
+			tmc_mapping: r.read_encoded()?,
+			tmc_next: r.read_encoded()?,
+			tmc_points: r.read_encoded()?,
+			edge_names: FlatVec::new(),
+			edge_refs: FlatVec::new(),
+			edge_classes: FlatVec::new(),
+		};
+
+		let edge_index = ::rtree::RTree { boxes: r.read_section()?, level_offsets: r.read_section()?, items: r.read_section()? };
 
-		(lat_bin as usize, lon_bin as usize)
+		routing_data.edge_names = r.read_section()?;
+		routing_data.edge_refs = r.read_section()?;
+		routing_data.edge_classes = r.read_section()?;
+		let names = ::strings::StringTable { bytes: r.read_section()?, offsets: r.read_section()? };
+		let addresses = r.read_section()?;
+		let address_index = ::rtree::RTree { boxes: r.read_section()?, level_offsets: r.read_section()?, items: r.read_section()? };
+
+		Ok(State { routing_data: routing_data, edge_index: edge_index, names: names, addresses: addresses, address_index: address_index })
 	}
 
-	pub fn find_closest_node(&self, position: &Position, routing_data: &RoutingData) -> i64 {
-		let mut min_dist = f64::INFINITY;
-		let mut min_node = -1;
+	// consistency of the ids and offsets of a mapped state, which are used as indexes
+	pub fn validate(&self) -> Result<(), String> {
+		let routing_data = &self.routing_data;
+		let (nodes, edges) = (routing_data.internal_nodes.len(), routing_data.internal_edges.len());
+
+		if routing_data.node_positions.len() != nodes || routing_data.node_components.len() != nodes || routing_data.internal_offset.len() != nodes {
+			return Err("node sections differ in length".to_string());
+		}
+		if routing_data.edge_names.len() != edges || routing_data.edge_refs.len() != edges || routing_data.edge_classes.len() != edges {
+			return Err("edge sections differ in length".to_string());
+		}
+		if !is_monotonic(&routing_data.internal_offset, edges) {
+			return Err("invalid edge offsets".to_string());
+		}
+		// edges are grouped by their source node
+		for node in 0..nodes {
+			let (start, end) = routing_data.edge_range(node);
+			if routing_data.internal_edges[start..end].iter().any(|edge| edge.source as usize != node || edge.target as usize >= nodes) {
+				return Err(format!("invalid edges of node {}", node));
+			}
+		}
+		if routing_data.tmc_mapping.values().any(|mapped| mapped.iter().any(|edge| *edge >= edges)) {
+			return Err("invalid tmc edges".to_string());
+		}
 
-		let (lat_bin, lon_bin) = self.calc_bin_index(position);
+		self.edge_index.validate(edges).map_err(|e| format!("edge index: {}", e))?;
+		self.address_index.validate(self.addresses.len()).map_err(|e| format!("address index: {}", e))?;
+		self.names.validate()
+	}
 
-		let start_bin_lat = cmp::max(0, lat_bin - 1);
-		let start_bin_lon = cmp::max(0, lon_bin - 1);
+	// projects position onto the closest edge the query accepts
+	pub fn find_closest_edge(&self, position: &Position, query: &SnapQuery) -> Option<Snap> {
+		self.find_closest_edges(position, query, 1).into_iter().next()
+	}
 
-		let end_bin_lat = cmp::min(self.bin_count_lat, lat_bin + 2);
-		let end_bin_lon = cmp::min(self.bin_count_lon, lon_bin + 2);
+	// up to k projections onto different edges, closest first
+	pub fn find_closest_edges(&self, position: &Position, query: &SnapQuery, k: usize) -> Vec<Snap> {
+		let routing_data = &self.routing_data;
+		let max_distance = query.radius.unwrap_or(f64::INFINITY);
 
-		for curr_bin_lat in start_bin_lat..end_bin_lat {
-			for curr_bin_lon in start_bin_lon..end_bin_lon {
-				let bin_index = self.calc_bin_position(curr_bin_lat, curr_bin_lon);
+		let closest = self.edge_index.nearest(position, k, max_distance, |edge_id| {
+			let edge = &routing_data.internal_edges[edge_id as usize];
 
-				for node in &self.bins[bin_index].nodes {
-					if let Some(candidate) = routing_data.osm_nodes.get(node) {
-						let candidate_distance = position.distance(&candidate.position);
-						if candidate_distance < min_dist {
-							min_dist = candidate_distance;
-							min_node = *node;
-						}
-					}
-				}
+			// skip edges of the profile's islands and edges the profile can't use
+			if edge.constraints & query.constraints == 0 || routing_data.node_components[edge.source as usize] & query.constraints == 0 {
+				return None;
 			}
-		}
-		min_node
+
+			let (source, target) = (routing_data.position(edge.source as usize), routing_data.position(edge.target as usize));
+			if !query.accepts_bearing(source.bearing(&target)) {
+				return None;
+			}
+
+			Some(position.distance(&position.project_onto(&source, &target).1))
+		});
+
+		closest.into_iter().map(|(edge_id, distance)| {
+			let (ratio, projected) = routing_data.project_onto_edge(edge_id as usize, position);
+
+			Snap { edge: edge_id as usize, ratio: ratio, position: projected, distance: distance, directed: query.bearing.is_some() }
+		}).collect()
+	}
+	// projection onto the closest edge carrying a name or ref, regardless of the profiles
+	pub fn find_closest_named_edge(&self, position: &Position, max_distance: f64) -> Option<Snap> {
+		let routing_data = &self.routing_data;
+
+		let closest = self.edge_index.nearest(position, 1, max_distance, |edge_id| {
+			if routing_data.edge_names[edge_id as usize] == ::strings::NONE && routing_data.edge_refs[edge_id as usize] == ::strings::NONE {
+				return None;
+			}
+			Some(position.distance(&routing_data.project_onto_edge(edge_id as usize, position).1))
+		});
+
+		closest.into_iter().next().map(|(edge_id, distance)| {
+			let (ratio, projected) = routing_data.project_onto_edge(edge_id as usize, position);
+
+			Snap { edge: edge_id as usize, ratio: ratio, position: projected, distance: distance, directed: false }
+		})
+	}
+
+	// id and distance of the closest address
+	pub fn find_closest_address(&self, position: &Position, max_distance: f64) -> Option<(usize, f64)> {
+		let addresses = &self.addresses;
+
+		self.address_index.nearest(position, 1, max_distance, |id| Some(position.distance(&addresses[id as usize].position.position())))
+			.into_iter().next().map(|(id, distance)| (id as usize, distance))
 	}
 }
 
-#[derive(Debug, RustcEncodable, RustcDecodable)]
-pub struct BoundingBox {
-	pub min_lat: f64,
-	pub min_lon: f64,
-	pub max_lat: f64,
-	pub max_lon: f64
-}
\ No newline at end of file
+#[test]
+fn test_reverse_lookup() {
+	let positions: Vec<Position> = (0..5).map(|i| Position { lat: 48.0, lon: 9.0 + i as f64 * 0.001 }).collect();
+	let mut data = ::parser::build_road_data(&positions);
+
+	let mut names = ::strings::StringTableBuilder::new();
+	let street = names.add("Hauptstraße");
+	let housenumber = names.add("12a");
+
+	// only the edges of the last segment carry a name
+	for edge in 0..data.routing_data.internal_edges.len() {
+		let ref routing_edge = data.routing_data.internal_edges[edge];
+		if routing_edge.source.max(routing_edge.target) == 4 {
+			data.routing_data.edge_names[edge] = street;
+		}
+	}
+
+	let address = Address { position: RoutingNode::from_position(&Position { lat: 48.0003, lon: 9.0005 }), housenumber: housenumber, street: ::strings::NONE };
+	data.address_index = ::rtree::RTree::build(&[::rtree::Rect::from_nodes(&address.position, &address.position)]);
+	data.addresses = vec![address].into();
+	data.names = names.table;
+
+	let query = Position { lat: 48.0001, lon: 9.0005 };
+
+	let snap = data.find_closest_named_edge(&query, 1000.0).unwrap();
+	assert_eq!(data.names.get(data.routing_data.edge_names[snap.edge]), Some("Hauptstraße"));
+	assert!(snap.position.lon >= 9.003);
+	assert!(data.find_closest_named_edge(&query, 100.0).is_none());
+
+	let (id, distance) = data.find_closest_address(&query, 100.0).unwrap();
+	assert_eq!(data.names.get(data.addresses[id].housenumber), Some("12a"));
+	assert!((distance - 22.2).abs() < 0.5);
+	assert!(data.find_closest_address(&query, 10.0).is_none());
+}
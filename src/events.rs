@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+pub const DEFAULT_DIR: &'static str = "rds";
+pub const DEFAULT_LANGUAGE: &'static str = "en";
+
+const EVENTS_FILE: &'static str = "events.tsv";
+const CLASSES_FILE: &'static str = "event_classes.tsv";
+const PROFILE_COLUMNS: [&'static str; 3] = ["car", "bike", "walk"];
+const MAX_QUANTIFIER_TYPE: u32 = 12;
+
+// an ALERT-C update class (ISO 14819-2) and the default effects of its events
+#[derive(Debug, Clone)]
+pub struct UpdateClass {
+	// roadworks, closures and restrictions, the events of all other classes are dynamic
+	pub longer_lasting: bool,
+	pub slowdown: ::data::Slowdown,
+}
+
+#[derive(Debug, Clone)]
+pub struct EventInfo {
+	pub class: u32,
+	pub forecast: bool,
+	// removes the messages of all update classes at its location
+	pub cancels: bool,
+	// quantifier type (ISO 14819-2) of the Q in the description
+	pub quantifier: Option<u32>,
+	// the event's own slowdown where given, the class default otherwise
+	pub slowdown: ::data::Slowdown,
+	// language -> description, "en" is always present
+	pub descs: HashMap<String, String>,
+}
+
+#[derive(Debug)]
+pub struct EventTable {
+	pub classes: HashMap<u32, UpdateClass>,
+	pub events: HashMap<u32, EventInfo>,
+}
+
+impl EventTable {
+	// the directory contains events.tsv and event_classes.tsv
+	pub fn read_dir(dir: &str) -> Result<EventTable, String> {
+		let dir = Path::new(dir);
+		let mut classes = HashMap::new();
+
+		for record in &read_records(&dir.join(CLASSES_FILE))? {
+			let class = field_u32(record, "class").ok_or(format!("{}: invalid class {:?}", CLASSES_FILE, record.get("class")))?;
+			let slowdown = field_slowdown(record, &[0.0; 3])?;
+			classes.insert(class, UpdateClass { longer_lasting: record.get("duration").map(|d| d == "L").unwrap_or(false), slowdown: slowdown });
+		}
+
+		let mut events = HashMap::new();
+		for record in &read_records(&dir.join(EVENTS_FILE))? {
+			let code = field_u32(record, "code").ok_or(format!("{}: invalid code {:?}", EVENTS_FILE, record.get("code")))?;
+			let class = field_u32(record, "class").filter(|class| classes.contains_key(class)).ok_or(format!("{}: unknown update class of event {}", EVENTS_FILE, code))?;
+			let slowdown = field_slowdown(record, &classes[&class].slowdown)?;
+
+			// every column after the profiles is a language
+			let descs: HashMap<String, String> = record.iter().filter(|&(column, desc)| !is_known_column(column) && !desc.is_empty()).map(|(column, desc)| (column.clone(), desc.clone())).collect();
+			if !descs.contains_key(DEFAULT_LANGUAGE) {
+				return Err(format!("{}: event {} has no {} description", EVENTS_FILE, code, DEFAULT_LANGUAGE));
+			}
+
+			let forecast = record.get("nature").map(|n| n == "F").unwrap_or(false);
+			let cancels = record.get("cancel").map(|c| c == "1").unwrap_or(false);
+			let quantifier = match record.get("quantifier").map(|q| q.as_str()) {
+				None | Some("") => None,
+				Some(q) => Some(q.parse::<u32>().ok().filter(|q| *q <= MAX_QUANTIFIER_TYPE).ok_or(format!("{}: invalid quantifier type of event {}: {}", EVENTS_FILE, code, q))?),
+			};
+			events.insert(code, EventInfo { class: class, forecast: forecast, cancels: cancels, quantifier: quantifier, slowdown: slowdown, descs: descs });
+		}
+
+		Ok(EventTable { classes: classes, events: events })
+	}
+
+	// falls back to the english description
+	pub fn desc(&self, event: u32, language: &str) -> Option<&str> {
+		let descs = &self.events.get(&event)?.descs;
+		descs.get(language).or(descs.get(DEFAULT_LANGUAGE)).map(|desc| desc.as_str())
+	}
+
+	pub fn longer_lasting(&self, event: u32) -> bool {
+		self.events.get(&event).and_then(|info| self.classes.get(&info.class)).map(|class| class.longer_lasting).unwrap_or(false)
+	}
+}
+
+fn is_known_column(column: &str) -> bool {
+	["code", "class", "nature", "cancel", "quantifier"].contains(&column) || PROFILE_COLUMNS.contains(&column)
+}
+
+// tab separated utf-8 with a header line, lines starting with # are comments
+fn read_records(path: &Path) -> Result<Vec<HashMap<String, String>>, String> {
+	let content = fs::read_to_string(path).map_err(|e| format!("can't read {}: {}", path.display(), e))?;
+	let mut lines = content.lines().filter(|line| !line.trim().is_empty() && !line.starts_with('#'));
+
+	let header: Vec<String> = match lines.next() {
+		Some(line) => line.split('\t').map(|name| name.trim().to_lowercase()).collect(),
+		None => return Ok(Vec::new()),
+	};
+
+	Ok(lines.map(|line| header.iter().cloned().zip(line.split('\t').map(|value| value.trim().to_string())).collect()).collect())
+}
+
+fn field_u32(record: &HashMap<String, String>, name: &str) -> Option<u32> {
+	record.get(name).and_then(|value| value.parse::<u32>().ok())
+}
+
+// empty profile columns keep the default
+fn field_slowdown(record: &HashMap<String, String>, default: &::data::Slowdown) -> Result<::data::Slowdown, String> {
+	let mut result = *default;
+
+	for (i, column) in PROFILE_COLUMNS.iter().enumerate() {
+		match record.get(*column).map(|value| value.as_str()) {
+			None | Some("") => {},
+			Some(value) => result[i] = value.parse::<f64>().ok().filter(|slowdown| *slowdown >= 0.0 && *slowdown <= 1.0).ok_or(format!("invalid {} slowdown: {}", column, value))?,
+		}
+	}
+	Ok(result)
+}
+
+#[test]
+fn test_event_table() {
+	let dir = ::std::env::temp_dir().join(format!("tmc_events_{}", ::std::process::id()));
+	let _ = fs::create_dir_all(&dir);
+
+	fs::write(dir.join(CLASSES_FILE), "# comment\nclass\tname\tduration\tcar\tbike\twalk\n1\tlevel of service\tD\t\t\t\n5\tclosures and lane restrictions\tL\t1.0\t1.0\t\n").unwrap();
+	fs::write(dir.join(EVENTS_FILE), "code\tclass\tnature\tcancel\tquantifier\tcar\tbike\twalk\ten\tde\n\
+		101\t1\t\t\t\t0.95\t\t\tstationary traffic\tStau\n\
+		107\t1\tF\t\t\t0.95\t\t\tstationary traffic expected\t\n\
+		401\t5\t\t\t\t\t\t\tclosed\tgesperrt\n\
+		41\t5\t\t\t0\t0.4\t0\t\t(Q) overtaking lane(s) closed\t\n\
+		2028\t5\t\t1\t\t\t\t\tmessage cancelled\t\n").unwrap();
+
+	let table = EventTable::read_dir(dir.to_str().unwrap()).unwrap();
+	assert_eq!(table.events.len(), 5);
+
+	// a closure blocks cars and bikes but not pedestrians, lane closures only slow down cars
+	assert_eq!(table.events[&401].slowdown, [1.0, 1.0, 0.0]);
+	assert_eq!(table.events[&41].slowdown, [0.4, 0.0, 0.0]);
+	assert_eq!(table.events[&101].slowdown, [0.95, 0.0, 0.0]);
+	assert!(table.longer_lasting(401) && !table.longer_lasting(101));
+	assert!(table.events[&107].forecast && !table.events[&101].forecast);
+	assert!(table.events[&2028].cancels && !table.events[&401].cancels);
+	assert_eq!((table.events[&41].quantifier, table.events[&101].quantifier), (Some(0), None));
+
+	assert_eq!(table.desc(101, "de"), Some("Stau"));
+	assert_eq!(table.desc(107, "de"), Some("stationary traffic expected"));
+	assert_eq!(table.desc(101, "fr"), Some("stationary traffic"));
+	assert_eq!(table.desc(999, "en"), None);
+
+	// events must belong to a known class
+	fs::write(dir.join(EVENTS_FILE), "code\tclass\tnature\tcar\tbike\twalk\ten\n1\t2\t\t\t\t\ttraffic problem\n").unwrap();
+	assert!(EventTable::read_dir(dir.to_str().unwrap()).is_err());
+
+	// quantifier types go up to 12
+	fs::write(dir.join(EVENTS_FILE), "code\tclass\tquantifier\ten\n1\t1\t13\ttraffic problem (Q)\n").unwrap();
+	assert!(EventTable::read_dir(dir.to_str().unwrap()).is_err());
+	let _ = fs::remove_dir_all(&dir);
+}
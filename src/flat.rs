@@ -0,0 +1,328 @@
+use std::fs::File;
+use std::io;
+use std::io::{Write, BufWriter, Cursor};
+use std::mem;
+use std::slice;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use std::fmt;
+
+use memmap::Mmap;
+use rustc_serialize::{Encodable, Decodable, Encoder, Decoder};
+use bincode::rustc_serialize::{encode_into, decode_from};
+
+const MAGIC: &'static [u8; 8] = b"FAPRAOSM";
+const FORMAT_VERSION: u32 = 2;
+const BYTE_ORDER_MARK: u32 = 0x01020304;
+const SECTION_ALIGN: u64 = 64;
+
+// element storage which is either owned (during import) or borrowed from a mapped state file
+pub enum FlatVec<T> {
+	Owned(Vec<T>),
+	Mapped { ptr: *const T, len: usize, _map: Arc<Mmap> },
+}
+
+// the mapped memory is read only and kept alive by the Arc
+unsafe impl<T: Send + Sync> Send for FlatVec<T> {}
+unsafe impl<T: Send + Sync> Sync for FlatVec<T> {}
+
+impl<T> FlatVec<T> {
+	pub fn new() -> FlatVec<T> {
+		FlatVec::Owned(Vec::new())
+	}
+
+	pub fn is_mapped(&self) -> bool {
+		match *self {
+			FlatVec::Owned(_) => false,
+			FlatVec::Mapped { .. } => true,
+		}
+	}
+}
+
+impl<T: Copy> FlatVec<T> {
+	pub fn push(&mut self, value: T) {
+		self.make_owned().push(value);
+	}
+
+	// mapped data is copied on the first write access
+	fn make_owned(&mut self) -> &mut Vec<T> {
+		if self.is_mapped() {
+			let owned = self.to_vec();
+			*self = FlatVec::Owned(owned);
+		}
+
+		match *self {
+			FlatVec::Owned(ref mut vec) => vec,
+			FlatVec::Mapped { .. } => unreachable!(),
+		}
+	}
+}
+
+impl<T> From<Vec<T>> for FlatVec<T> {
+	fn from(vec: Vec<T>) -> FlatVec<T> {
+		FlatVec::Owned(vec)
+	}
+}
+
+impl<T> Deref for FlatVec<T> {
+	type Target = [T];
+
+	fn deref(&self) -> &[T] {
+		match *self {
+			FlatVec::Owned(ref vec) => vec,
+			FlatVec::Mapped { ptr, len, .. } => unsafe { slice::from_raw_parts(ptr, len) },
+		}
+	}
+}
+
+impl<T: Copy> DerefMut for FlatVec<T> {
+	fn deref_mut(&mut self) -> &mut [T] {
+		self.make_owned()
+	}
+}
+
+impl<T: fmt::Debug> fmt::Debug for FlatVec<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.deref().fmt(f)
+	}
+}
+
+impl<T: Encodable> Encodable for FlatVec<T> {
+	fn encode<S: Encoder>(&self, s: &mut S) -> Result<(), S::Error> {
+		self.deref().encode(s)
+	}
+}
+
+impl<T: Decodable> Decodable for FlatVec<T> {
+	fn decode<D: Decoder>(d: &mut D) -> Result<FlatVec<T>, D::Error> {
+		Vec::decode(d).map(FlatVec::Owned)
+	}
+}
+
+// element types which can be written to and read from a section as they are laid out in memory
+pub unsafe trait FlatItem: Copy {
+	fn write_flat<W: Write>(&self, w: &mut W) -> io::Result<()>;
+}
+
+pub fn write_raw<W: Write, T: Copy>(value: &T, w: &mut W) -> io::Result<()> {
+	let bytes = unsafe { slice::from_raw_parts(value as *const T as *const u8, mem::size_of::<T>()) };
+	w.write_all(bytes)
+}
+
+macro_rules! flat_primitive {
+	($($t:ty),*) => {
+		$(unsafe impl FlatItem for $t {
+			fn write_flat<W: Write>(&self, w: &mut W) -> io::Result<()> {
+				write_raw(self, w)
+			}
+		})*
+	}
+}
+
+flat_primitive!(u8, u32, i32, i64, f32);
+
+#[derive(Debug)]
+struct SectionHeader {
+	elem_size: u64,
+	offset: u64,
+	len: u64,
+}
+
+pub struct FlatWriter<W: Write> {
+	writer: W,
+	position: u64,
+	sections: Vec<SectionHeader>,
+}
+
+impl<W: Write> FlatWriter<W> {
+	pub fn write_section<T: FlatItem>(&mut self, items: &[T]) -> io::Result<()> {
+		self.pad()?;
+
+		let offset = self.position;
+		for item in items {
+			item.write_flat(&mut self.writer)?;
+		}
+		self.position += (items.len() * mem::size_of::<T>()) as u64;
+
+		self.sections.push(SectionHeader { elem_size: mem::size_of::<T>() as u64, offset: offset, len: items.len() as u64 });
+		Ok(())
+	}
+
+	// non-flat data (maps, small structs) is stored bincode encoded in a byte section
+	pub fn write_encoded<T: Encodable>(&mut self, value: &T) -> io::Result<()> {
+		let mut buffer = Vec::new();
+		encode_into(value, &mut buffer, ::bincode::SizeLimit::Infinite).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+		self.write_section(&buffer)
+	}
+
+	fn pad(&mut self) -> io::Result<()> {
+		while self.position % SECTION_ALIGN != 0 {
+			self.writer.write_all(&[0])?;
+			self.position += 1;
+		}
+		Ok(())
+	}
+}
+
+pub struct FlatReader {
+	map: Arc<Mmap>,
+	sections: Vec<SectionHeader>,
+	next: usize,
+}
+
+impl FlatReader {
+	pub fn read_section<T: FlatItem>(&mut self) -> io::Result<FlatVec<T>> {
+		let (offset, len) = self.next_section(mem::size_of::<T>() as u64)?;
+
+		if offset % mem::align_of::<T>() != 0 {
+			return Err(invalid_data("misaligned section"));
+		}
+
+		let ptr = unsafe { self.map.as_ptr().offset(offset as isize) as *const T };
+		Ok(FlatVec::Mapped { ptr: ptr, len: len, _map: self.map.clone() })
+	}
+
+	pub fn read_encoded<T: Decodable>(&mut self) -> io::Result<T> {
+		let (offset, len) = self.next_section(1)?;
+		let mut cursor = Cursor::new(&self.map[offset..offset + len]);
+		decode_from(&mut cursor, ::bincode::SizeLimit::Infinite).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+	}
+
+	fn next_section(&mut self, elem_size: u64) -> io::Result<(usize, usize)> {
+		let section = match self.sections.get(self.next) {
+			Some(section) => section,
+			None => return Err(invalid_data("missing section")),
+		};
+		self.next += 1;
+
+		if section.elem_size != elem_size {
+			return Err(invalid_data("unexpected element size"));
+		}
+		let end = section.len.checked_mul(section.elem_size).and_then(|size| size.checked_add(section.offset));
+		if end.map(|end| end > self.map.len() as u64).unwrap_or(true) {
+			return Err(invalid_data("section out of bounds"));
+		}
+
+		Ok((section.offset as usize, section.len as usize))
+	}
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+// layout: magic | version | byte order mark | section count | section table | aligned sections
+// the first section always holds the encoded state header
+pub fn write_state(filename: &str, header: &::data::StateHeader, data: &::data::State) -> io::Result<()> {
+	// a first pass into a sink computes the section table, the second one writes the sections
+	let mut layout = FlatWriter { writer: io::sink(), position: 0, sections: Vec::new() };
+	layout.write_encoded(header)?;
+	data.write_flat(&mut layout)?;
+
+	let header_len = (8 + 4 + 4 + 4 + layout.sections.len() * 3 * 8) as u64;
+	let shift = (header_len + SECTION_ALIGN - 1) / SECTION_ALIGN * SECTION_ALIGN;
+
+	let mut writer = BufWriter::new(File::create(filename)?);
+	writer.write_all(MAGIC)?;
+	write_raw(&FORMAT_VERSION, &mut writer)?;
+	write_raw(&BYTE_ORDER_MARK, &mut writer)?;
+	write_raw(&(layout.sections.len() as u32), &mut writer)?;
+	for section in &layout.sections {
+		write_raw(&section.elem_size, &mut writer)?;
+		write_raw(&(section.offset + shift), &mut writer)?;
+		write_raw(&section.len, &mut writer)?;
+	}
+	for _ in header_len..shift {
+		writer.write_all(&[0])?;
+	}
+
+	let mut flat = FlatWriter { writer: writer, position: shift, sections: Vec::new() };
+	flat.write_encoded(header)?;
+	data.write_flat(&mut flat)?;
+	flat.writer.flush()
+}
+
+pub fn read_state(filename: &str, expected: &::data::StateHeader) -> io::Result<(::data::StateHeader, ::data::State)> {
+	let file = File::open(filename)?;
+	let map = Arc::new(unsafe { Mmap::map(&file) }?);
+
+	if map.len() < 20 || &map[0..8] != MAGIC {
+		return Err(invalid_data("not a flat state file"));
+	}
+
+	let version = read_u32(&map, 8);
+	let byte_order = read_u32(&map, 12);
+	let section_cnt = read_u32(&map, 16) as usize;
+
+	if version != FORMAT_VERSION {
+		return Err(invalid_data(&format!("unsupported format version {}, expected {}", version, FORMAT_VERSION)));
+	}
+	if byte_order != BYTE_ORDER_MARK {
+		return Err(invalid_data("state file was written on a machine with different byte order"));
+	}
+	if section_cnt.checked_mul(24).and_then(|size| size.checked_add(20)).map(|size| map.len() < size).unwrap_or(true) {
+		return Err(invalid_data("truncated section table"));
+	}
+
+	let mut sections = Vec::new();
+	for i in 0..section_cnt {
+		let base = 20 + i * 24;
+		sections.push(SectionHeader { elem_size: read_u64(&map, base), offset: read_u64(&map, base + 8), len: read_u64(&map, base + 16) });
+	}
+
+	let mut reader = FlatReader { map: map, sections: sections, next: 0 };
+
+	let header: ::data::StateHeader = reader.read_encoded()?;
+	if let Err(e) = header.check(expected) {
+		return Err(invalid_data(&e));
+	}
+
+	let data = ::data::State::read_flat(&mut reader)?;
+	// the ids stored in the sections are used as indexes without further checks
+	if let Err(e) = data.validate() {
+		return Err(invalid_data(&format!("corrupt state file: {}", e)));
+	}
+	Ok((header, data))
+}
+
+fn read_u32(map: &Mmap, offset: usize) -> u32 {
+	let mut value = 0u32;
+	unsafe { ::std::ptr::copy_nonoverlapping(map.as_ptr().offset(offset as isize), &mut value as *mut u32 as *mut u8, 4) };
+	value
+}
+
+fn read_u64(map: &Mmap, offset: usize) -> u64 {
+	let mut value = 0u64;
+	unsafe { ::std::ptr::copy_nonoverlapping(map.as_ptr().offset(offset as isize), &mut value as *mut u64 as *mut u8, 8) };
+	value
+}
+
+#[test]
+fn test_state_round_trip() {
+	let positions: Vec<::data::Position> = (0..4).map(|i| ::data::Position { lat: 48.0, lon: 9.0 + i as f64 * 0.001 }).collect();
+	let mut data = ::parser::build_road_data(&positions);
+	let location = ::data::TMCLocation::new(::data::DEFAULT_TMC_TABLE, 100);
+	data.routing_data.tmc_mapping.insert(location, vec![0, 1].into_iter().collect());
+
+	let path = ::std::env::temp_dir().join(format!("flat_state_{}.bin", ::std::process::id()));
+	let filename = path.to_str().unwrap();
+	let header = ::data::StateHeader::new(&None, 42);
+	write_state(filename, &header, &data).unwrap();
+
+	let (read_header, read) = read_state(filename, &header).unwrap();
+	assert_eq!(read_header.profile_hash, 42);
+	assert!(read.routing_data.internal_edges.is_mapped());
+	assert_eq!(read.routing_data.internal_nodes.to_vec(), data.routing_data.internal_nodes.to_vec());
+	assert_eq!(read.routing_data.internal_offset.to_vec(), data.routing_data.internal_offset.to_vec());
+	assert_eq!(read.routing_data.internal_edges.iter().map(|edge| (edge.source, edge.target)).collect::<Vec<_>>(), data.routing_data.internal_edges.iter().map(|edge| (edge.source, edge.target)).collect::<Vec<_>>());
+	assert_eq!(read.routing_data.edge_classes.to_vec(), data.routing_data.edge_classes.to_vec());
+	assert_eq!(read.routing_data.tmc_mapping, data.routing_data.tmc_mapping);
+	assert_eq!(read.edge_index.items.to_vec(), data.edge_index.items.to_vec());
+
+	// another profile and edges pointing behind the last node are rejected
+	assert!(read_state(filename, &::data::StateHeader::new(&None, 43)).is_err());
+	data.routing_data.internal_edges[0].target = positions.len() as u32;
+	write_state(filename, &header, &data).unwrap();
+	assert!(read_state(filename, &header).is_err());
+	let _ = ::std::fs::remove_file(&path);
+}
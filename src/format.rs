@@ -0,0 +1,146 @@
+use std::collections::BTreeMap;
+use rustc_serialize::json;
+use rustc_serialize::json::{Json, ToJson};
+use time::{Timespec, at_utc};
+
+// output formats of a route, json is the plain encoding of ::routing::Route
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RouteFormat {
+	Json,
+	GeoJson,
+	Gpx,
+	// the json encoding with the path as encoded polyline of precision 5 or 6
+	Polyline(u32),
+}
+
+impl RouteFormat {
+	pub fn parse(raw: &str) -> Option<RouteFormat> {
+		match raw {
+			"json" => Some(RouteFormat::Json),
+			"geojson" => Some(RouteFormat::GeoJson),
+			"gpx" => Some(RouteFormat::Gpx),
+			"polyline5" => Some(RouteFormat::Polyline(5)),
+			"polyline6" => Some(RouteFormat::Polyline(6)),
+			_ => None,
+		}
+	}
+
+	pub fn content_type(&self) -> &'static str {
+		match *self {
+			RouteFormat::Json | RouteFormat::Polyline(_) => "application/json",
+			RouteFormat::GeoJson => "application/geo+json",
+			RouteFormat::Gpx => "application/gpx+xml",
+		}
+	}
+}
+
+#[derive(Debug, Clone, RustcEncodable)]
+pub struct PolylineRoute {
+	pub distance: f64,
+	pub time: f64,
+	pub polyline: String,
+	pub precision: u32,
+	pub times: Vec<f64>,
+}
+
+impl PolylineRoute {
+	pub fn from_route(route: &::routing::Route, precision: u32) -> PolylineRoute {
+		PolylineRoute { distance: route.distance, time: route.time, polyline: encode_polyline(&route.path, precision), precision: precision, times: route.times.clone() }
+	}
+}
+
+// google encoded polyline algorithm, points are [lat, lon]
+pub fn encode_polyline(path: &[[f64; 2]], precision: u32) -> String {
+	let factor = 10f64.powi(precision as i32);
+	let mut result = String::new();
+	let mut previous = [0i64, 0i64];
+
+	for point in path {
+		for i in 0..2 {
+			let value = (point[i] * factor).round() as i64;
+			encode_polyline_value(value - previous[i], &mut result);
+			previous[i] = value;
+		}
+	}
+
+	result
+}
+
+fn encode_polyline_value(value: i64, result: &mut String) {
+	let mut value = if value < 0 { !(value << 1) } else { value << 1 };
+
+	while value >= 0x20 {
+		result.push((((value & 0x1f) | 0x20) as u8 + 63) as char);
+		value >>= 5;
+	}
+	result.push((value as u8 + 63) as char);
+}
+
+fn positions(route: &::routing::Route) -> Vec<::data::Position> {
+	route.path.iter().map(|point| ::data::Position { lat: point[0], lon: point[1] }).collect()
+}
+
+// a LineString feature, the per-point times are added as a property
+fn route_to_geojson(route: &::routing::Route, mut properties: BTreeMap<String, Json>) -> Json {
+	properties.insert("distance".to_string(), route.distance.to_json());
+	properties.insert("time".to_string(), route.time.to_json());
+	properties.insert("times".to_string(), route.times.to_json());
+
+	::geojson::feature(::geojson::line_string(&positions(route)), properties)
+}
+
+// the route feature in a collection, which stays empty without a route. used by the cli and the route api
+pub fn route_collection(route: Option<&::routing::Route>, properties: BTreeMap<String, Json>) -> Json {
+	::geojson::feature_collection(route.into_iter().map(|route| route_to_geojson(route, properties.clone())).collect())
+}
+
+// a single track whose points are timestamped from the departure on
+pub fn route_to_gpx(route: Option<&::routing::Route>, departure: Timespec) -> String {
+	let mut gpx = String::new();
+	gpx.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+	gpx.push_str("<gpx version=\"1.1\" creator=\"fapra_osm\" xmlns=\"http://www.topografix.com/GPX/1/1\">\n");
+
+	if let Some(route) = route {
+		gpx.push_str(&format!("  <metadata><time>{}</time></metadata>\n", at_utc(departure).rfc3339()));
+		gpx.push_str(&format!("  <trk>\n    <name>route {:.0} m, {:.0} s</name>\n    <trkseg>\n", route.distance, route.time));
+
+		for (point, time) in route.path.iter().zip(route.times.iter()) {
+			let point_time = at_utc(Timespec::new(departure.sec + time.round() as i64, 0));
+			gpx.push_str(&format!("      <trkpt lat=\"{:.7}\" lon=\"{:.7}\"><time>{}</time></trkpt>\n", point[0], point[1], point_time.rfc3339()));
+		}
+
+		gpx.push_str("    </trkseg>\n  </trk>\n");
+	}
+
+	gpx.push_str("</gpx>\n");
+	gpx
+}
+
+// used by the cli, which has no additional response fields
+pub fn encode_route(route: &::routing::Route, format: RouteFormat, departure: Timespec) -> String {
+	match format {
+		RouteFormat::Json => json::encode(route).unwrap(),
+		RouteFormat::GeoJson => route_collection(Some(route), BTreeMap::new()).to_string(),
+		RouteFormat::Gpx => route_to_gpx(Some(route), departure),
+		RouteFormat::Polyline(precision) => json::encode(&PolylineRoute::from_route(route, precision)).unwrap(),
+	}
+}
+
+#[test]
+fn test_route_formats() {
+	let route = ::routing::Route { distance: 100.0, time: 10.0, path: vec![[48.0, 9.0], [48.0005, 9.0005], [48.001, 9.001]], times: vec![0.0, 5.0, 10.0], edges: Vec::new() };
+
+	let geojson = Json::from_str(&encode_route(&route, RouteFormat::GeoJson, Timespec::new(0, 0))).unwrap();
+	let feature = &geojson.find("features").unwrap()[0];
+	assert_eq!(feature.find_path(&["geometry", "type"]).unwrap().as_string(), Some("LineString"));
+	assert_eq!(feature.find_path(&["geometry", "coordinates"]).unwrap()[0], Json::Array(vec![9.0.to_json(), 48.0.to_json()]));
+	assert_eq!(route_collection(None, BTreeMap::new()).find("features").unwrap().as_array().map(|features| features.len()), Some(0));
+
+	// example from the polyline algorithm documentation
+	assert_eq!(encode_polyline(&[[38.5, -120.2], [40.7, -120.95], [43.252, -126.453]], 5), "_p~iF~ps|U_ulLnnqC_mqNvxq`@");
+	assert_eq!(encode_polyline(&[[38.5, -120.2]], 6), "_izlhA~rlgdF");
+
+	let gpx = encode_route(&route, RouteFormat::Gpx, Timespec::new(1000000000, 0));
+	assert_eq!(gpx.matches("<trkpt").count(), 3);
+	assert!(gpx.contains("<time>2001-09-09T01:46:50Z</time></trkpt>"));
+}
@@ -0,0 +1,33 @@
+use std::collections::BTreeMap;
+use rustc_serialize::json::{Json, ToJson};
+
+// geojson coordinates are [lon, lat]
+pub fn coordinate(position: &::data::Position) -> Json {
+	Json::Array(vec![position.lon.to_json(), position.lat.to_json()])
+}
+
+pub fn line_string(positions: &[::data::Position]) -> Json {
+	geometry("LineString", Json::Array(positions.iter().map(coordinate).collect()))
+}
+
+fn geometry(kind: &str, coordinates: Json) -> Json {
+	let mut object = BTreeMap::new();
+	object.insert("type".to_string(), kind.to_json());
+	object.insert("coordinates".to_string(), coordinates);
+	Json::Object(object)
+}
+
+pub fn feature(geometry: Json, properties: BTreeMap<String, Json>) -> Json {
+	let mut object = BTreeMap::new();
+	object.insert("type".to_string(), "Feature".to_json());
+	object.insert("geometry".to_string(), geometry);
+	object.insert("properties".to_string(), Json::Object(properties));
+	Json::Object(object)
+}
+
+pub fn feature_collection(features: Vec<Json>) -> Json {
+	let mut object = BTreeMap::new();
+	object.insert("type".to_string(), "FeatureCollection".to_json());
+	object.insert("features".to_string(), Json::Array(features));
+	Json::Object(object)
+}
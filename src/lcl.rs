@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+// meters around a location point in which edges are mapped to it
+const SNAP_RADIUS: f64 = 100.0;
+const SNAP_CANDIDATES: usize = 8;
+
+// the parts of the official tmc location code list (LCL) needed for routing
+#[derive(Debug)]
+pub struct LocationTable {
+	pub points: HashMap<::data::TMCLocation, ::data::TMCPoint>,
+	// location -> negative and positive offset within the same table, 0 if the chain ends there
+	pub offsets: HashMap<::data::TMCLocation, (u32, u32)>,
+}
+
+impl LocationTable {
+	// the directory contains the LCL files POINTS, POFFSETS and optionally ROADS and SEGMENTS (.DAT or .csv)
+	pub fn read_dir(dir: &str) -> Result<LocationTable, String> {
+		let files = list_files(dir)?;
+		let records = |name: &str, required: bool| -> Result<Vec<HashMap<String, String>>, String> {
+			match files.get(name) {
+				Some(path) => read_records(path),
+				None if required => Err(format!("{} not found in location table {}", name, dir)),
+				None => Ok(Vec::new()),
+			}
+		};
+
+		// road numbers of roads and segments, points refer to either
+		let mut road_numbers = HashMap::new();
+		for record in records("ROADS", false)?.iter().chain(records("SEGMENTS", false)?.iter()) {
+			if let (Some(location), Some(number)) = (field_location(record), record.get("ROADNUMBER")) {
+				if !number.is_empty() {
+					road_numbers.insert(location, number.clone());
+				}
+			}
+		}
+
+		let mut points = HashMap::new();
+		for record in &records("POINTS", true)? {
+			let (location, lon, lat) = match (field_location(record), field_coordinate(record, "XCOORD"), field_coordinate(record, "YCOORD")) {
+				(Some(location), Some(lon), Some(lat)) => (location, lon, lat),
+				_ => continue,
+			};
+
+			let road = field_u32(record, "ROA_LCD").unwrap_or(0);
+			let segment = field_u32(record, "SEG_LCD").unwrap_or(0);
+			let number = road_numbers.get(&::data::TMCLocation::new(location.table, road)).or(road_numbers.get(&::data::TMCLocation::new(location.table, segment))).cloned().unwrap_or(String::new());
+			points.insert(location, ::data::TMCPoint { position: ::data::Position { lat: lat, lon: lon }, road: road, road_number: number });
+		}
+
+		let mut offsets = HashMap::new();
+		for record in &records("POFFSETS", true)? {
+			if let Some(location) = field_location(record) {
+				offsets.insert(location, (field_u32(record, "NEG_OFF_LCD").unwrap_or(0), field_u32(record, "POS_OFF_LCD").unwrap_or(0)));
+			}
+		}
+
+		Ok(LocationTable { points: points, offsets: offsets })
+	}
+}
+
+// upper case file stem -> path
+fn list_files(dir: &str) -> Result<HashMap<String, ::std::path::PathBuf>, String> {
+	let entries = fs::read_dir(dir).map_err(|e| format!("can't read location table {}: {}", dir, e))?;
+	let mut result = HashMap::new();
+
+	for entry in entries.filter_map(|entry| entry.ok()) {
+		let path = entry.path();
+		if let Some(stem) = path.file_stem().map(|stem| stem.to_string_lossy().to_uppercase()) {
+			result.insert(stem, path);
+		}
+	}
+	Ok(result)
+}
+
+// semicolon separated with a header line, the files are latin-1 encoded
+fn read_records(path: &Path) -> Result<Vec<HashMap<String, String>>, String> {
+	let bytes = fs::read(path).map_err(|e| format!("can't read {}: {}", path.display(), e))?;
+	let content: String = bytes.iter().map(|byte| *byte as char).collect();
+	let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+
+	let header: Vec<String> = match lines.next() {
+		Some(line) => line.split(';').map(|name| name.trim().to_uppercase()).collect(),
+		None => return Ok(Vec::new()),
+	};
+
+	Ok(lines.map(|line| header.iter().cloned().zip(line.split(';').map(|value| value.trim().to_string())).collect()).collect())
+}
+
+fn field_u32(record: &HashMap<String, String>, name: &str) -> Option<u32> {
+	record.get(name).and_then(|value| value.parse::<u32>().ok()).filter(|value| *value != 0)
+}
+
+// records without CID and TABCD belong to the default table
+fn field_location(record: &HashMap<String, String>) -> Option<::data::TMCLocation> {
+	let table = match (field_u32(record, "CID"), field_u32(record, "TABCD")) {
+		(Some(cid), Some(tabcd)) => ::data::TMCTable { cid: cid, tabcd: tabcd },
+		_ => ::data::DEFAULT_TMC_TABLE,
+	};
+	field_u32(record, "LCD").map(|lcd| ::data::TMCLocation::new(table, lcd))
+}
+
+// wgs84 in 1/100000 degrees with sign, e.g. +00915726
+fn field_coordinate(record: &HashMap<String, String>, name: &str) -> Option<f64> {
+	record.get(name).and_then(|value| value.trim_start_matches('+').parse::<i64>().ok()).map(|value| value as f64 / 100000.0)
+}
+
+// "A 8" and "A8" are the same road, osm refs may list several roads
+fn normalize_road_number(number: &str) -> String {
+	number.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_uppercase()
+}
+
+fn ref_matches(road_ref: Option<&str>, number: &str) -> bool {
+	match road_ref {
+		Some(road_ref) => road_ref.split(';').any(|part| normalize_road_number(part) == number),
+		None => false,
+	}
+}
+
+// the table's offset chains replace those of the osm tags, points without tagged ways are mapped onto the
+// car edges around their coordinates, preferring those whose ref matches the road number of the point
+pub fn apply(table: &LocationTable, state: &mut ::data::State) {
+	for (location, &(negative, positive)) in &table.offsets {
+		if positive != 0 {
+			state.routing_data.tmc_next.insert((*location, true), ::data::TMCLocation::new(location.table, positive));
+		}
+		if negative != 0 {
+			state.routing_data.tmc_next.insert((*location, false), ::data::TMCLocation::new(location.table, negative));
+		}
+	}
+
+	let mut query = ::data::SnapQuery::new(::data::FLAG_CAR);
+	query.radius = Some(SNAP_RADIUS);
+
+	let mut mapped = HashMap::new();
+	for (location, point) in &table.points {
+		if state.routing_data.tmc_mapping.contains_key(location) {
+			continue;
+		}
+
+		let candidates = state.find_closest_edges(&point.position, &query, SNAP_CANDIDATES);
+		let number = normalize_road_number(&point.road_number);
+		let matching: Vec<usize> = candidates.iter().filter(|snap| !number.is_empty() && ref_matches(state.names.get(state.routing_data.edge_refs[snap.edge]), &number)).map(|snap| snap.edge).collect();
+
+		let mut edges = HashSet::new();
+		let chosen = if matching.is_empty() { candidates.iter().map(|snap| snap.edge).take(1).collect() } else { matching };
+		for edge in chosen {
+			edges.insert(edge);
+			if let Some(reverse) = state.routing_data.reverse_edge(edge) {
+				edges.insert(reverse);
+			}
+		}
+
+		if !edges.is_empty() {
+			mapped.insert(*location, edges);
+		}
+	}
+
+	println!("L  | points:    {}", table.points.len());
+	println!("L  | offsets:   {}", table.offsets.len());
+	println!("L  | mapped:    {} of {} untagged points", mapped.len(), table.points.keys().filter(|location| !state.routing_data.tmc_mapping.contains_key(location)).count());
+
+	state.routing_data.tmc_mapping.extend(mapped);
+	for (location, point) in &table.points {
+		state.routing_data.tmc_points.insert(*location, point.clone());
+	}
+}
+
+#[test]
+fn test_location_table() {
+	let dir = ::std::env::temp_dir().join(format!("tmc_lcl_{}", ::std::process::id()));
+	let _ = fs::create_dir_all(&dir);
+
+	fs::write(dir.join("POINTS.DAT"), "CID;TABCD;LCD;CLASS;TCD;STCD;JUNCTIONNUMBER;RNID;N1ID;N2ID;POL_LCD;OTH_LCD;SEG_LCD;ROA_LCD;XCOORD;YCOORD\n\
+		58;1;12001;P;1;3;1;1;2;3;1;;;11000;+00900050;+4800000\n\
+		58;1;12002;P;1;3;2;1;2;3;1;;;11000;+00900250;+4800000\n\
+		58;1;12003;P;1;3;3;1;2;3;1;;;11000;+01000000;+5000000\n").unwrap();
+	fs::write(dir.join("poffsets.dat"), "CID;TABCD;LCD;NEG_OFF_LCD;POS_OFF_LCD\n58;1;12001;;12002\n58;1;12002;12001;12003\n").unwrap();
+	fs::write(dir.join("ROADS.DAT"), "CID;TABCD;LCD;CLASS;TCD;STCD;ROADNUMBER\n58;1;11000;L;1;1;A 8\n").unwrap();
+
+	let table = LocationTable::read_dir(dir.to_str().unwrap()).unwrap();
+	let location = |lcd: u32| ::data::TMCLocation::new(::data::TMCTable { cid: 58, tabcd: 1 }, lcd);
+	let _ = fs::remove_dir_all(&dir);
+	assert_eq!(table.points.len(), 3);
+	assert_eq!(table.points[&location(12002)].road_number, "A 8");
+	assert!((table.points[&location(12002)].position.lon - 9.0025).abs() < 1e-9);
+
+	let positions: Vec<::data::Position> = (0..4).map(|i| ::data::Position { lat: 48.0, lon: 9.0 + i as f64 * 0.001 }).collect();
+	let mut data = ::parser::build_road_data(&positions);
+	apply(&table, &mut data);
+
+	assert_eq!(data.routing_data.tmc_next.get(&(location(12002), true)), Some(&location(12003)));
+	assert_eq!(data.routing_data.tmc_next.get(&(location(12002), false)), Some(&location(12001)));
+	assert!(data.routing_data.tmc_next.get(&(location(12001), false)).is_none());
+
+	// both directions of the closest edge, the third point is far away from the road
+	assert_eq!(data.routing_data.tmc_mapping[&location(12001)].len(), 2);
+	assert_eq!(data.routing_data.tmc_mapping[&location(12002)].len(), 2);
+	assert!(data.routing_data.tmc_mapping[&location(12001)].is_disjoint(&data.routing_data.tmc_mapping[&location(12002)]));
+	assert!(!data.routing_data.tmc_mapping.contains_key(&location(12003)));
+	assert_eq!(data.routing_data.tmc_points.len(), 3);
+}
@@ -8,11 +8,13 @@ extern crate flate2;
 extern crate bincode;
 extern crate urlencoded;
 extern crate ordered_float;
+extern crate memmap;
 
 use std::ffi::OsString;
 use std::fs;
 use std::fs::File;
 use std::io::{BufWriter, BufReader};
+use std::sync::RwLock;
 
 use flate2::write::ZlibEncoder;
 use flate2::read::ZlibDecoder;
@@ -23,57 +25,217 @@ mod parser;
 mod server;
 mod data;
 mod tmc;
-
-const STATE_FILE_NAME: &'static str = "state.bin.gz";
+mod flat;
+mod routing;
+mod cli;
+mod batch;
+mod rtree;
+mod geojson;
+mod mvt;
+mod format;
+mod matching;
+mod strings;
+mod tmc_source;
+mod rds;
+mod lcl;
+mod events;
 
 fn main() {
-	let data = match fs::metadata(STATE_FILE_NAME) {
-		Ok(metadata) => {
-			if metadata.is_file() {
-				read_from_disk()
-			} else {
-				perform_parse()
-			}
-		},
-		Err(_) => perform_parse(),
+	let args: Vec<String> = std::env::args().skip(1).collect();
+
+	let command = match cli::parse_args(&args) {
+		Ok(command) => command,
+		Err(e) => {
+			println!("{}\n\n{}", e, cli::USAGE);
+			std::process::exit(2);
+		}
+	};
+
+	let result = match command {
+		cli::Command::Import { pbf, output, lcl } => run_import(&pbf, &output, &lcl),
+		cli::Command::Serve { state, pbf, lcl, config } => run_serve(&state, &pbf, &lcl, &config),
+		cli::Command::Route { state, source, target, vehicle, metric, format, simplify, tmc, tmc_events } => run_route(&state, &source, &target, &vehicle, &metric, format, simplify, &tmc, &tmc_events),
+		cli::Command::Batch { state, input, output, config } => run_batch(&state, &input, &output, &config),
+		cli::Command::Info { state } => run_info(&state),
+		cli::Command::Help => {
+			println!("{}", cli::USAGE);
+			Ok(())
+		}
 	};
 
-	server::start(data);
+	if let Err(e) = result {
+		println!("error: {}", e);
+		std::process::exit(1);
+	}
+}
+
+fn run_import(pbf: &OsString, output: &str, lcl: &Option<String>) -> Result<(), String> {
+	if !is_file(&pbf.to_string_lossy()) {
+		return Err(format!("pbf file {:?} not found", pbf));
+	}
 
-	//tmc::startRDS();
+	let table = load_location_table(lcl)?;
+	let header = data::StateHeader::new(&Some(pbf.clone()), parser::profile_hash());
+	perform_parse(pbf, header, output, table.as_ref());
+	Ok(())
 }
 
-fn perform_parse() -> data::State {
-	let default_file = OsString::from("/home/zsdn/baden-wuerttemberg-latest.osm.pbf".to_string());
-	//let default_file = OsString::from("/home/zsdn/germany-latest.osm.pbf".to_string());
+// lcl is only read if the state has to be rebuilt
+fn run_serve(state: &str, pbf: &Option<OsString>, lcl: &Option<String>, config: &server::ServerConfig) -> Result<(), String> {
+	let expected = data::StateHeader::new(pbf, parser::profile_hash());
 
-	let args: Vec<OsString> = std::env::args_os().collect();
-	let data = match args.len() {
-		1 => parser::read_file(&default_file),
-		2 => parser::read_file(&args[1]),
-		_ => build_dummy_data(),
+	let data = match load_state(state, &expected) {
+		Ok(data) => data,
+		Err(e) => match *pbf {
+			Some(ref pbf) => {
+				println!("Ignoring state file {}: {}", state, e);
+				let table = load_location_table(lcl)?;
+				perform_parse(pbf, expected, state, table.as_ref())
+			},
+			None => return Err(format!("can't load state file {} ({}), pass --pbf to build it", state, e)),
+		}
 	};
-	write_to_disk(&data);
+
+	server::start(data, config);
+	Ok(())
+}
+
+// tmc is a log or recording of tmc events which are applied before routing, tmc_events the directory of the event list
+fn run_route(state: &str, source: &str, target: &str, vehicle: &str, metric: &str, format: format::RouteFormat, simplify: Option<f64>, tmc: &Option<String>, tmc_events: &str) -> Result<(), String> {
+	let data = load_state(state, &data::StateHeader::new(&None, parser::profile_hash()))?;
+
+	let source_pos = routing::parse_position(source).ok_or(format!("invalid position: {}", source))?;
+	let target_pos = routing::parse_position(target).ok_or(format!("invalid position: {}", target))?;
+
+	let constraints = routing::vehicle_constraints(vehicle);
+	let query = data::SnapQuery::new(constraints);
+
+	let source_snap = data.find_closest_edge(&source_pos, &query).ok_or(format!("no edge usable by {} found near source", vehicle))?;
+	let target_snap = data.find_closest_edge(&target_pos, &query).ok_or(format!("no edge usable by {} found near target", vehicle))?;
+
+	let mut tmc_state = data::TMCState::new();
+	if let Some(ref path) = *tmc {
+		tmc::load_events(path, tmc_events, &mut tmc_state, &data)?;
+	}
+
+	let tmc_state = RwLock::new(tmc_state);
+	let route = routing::run_dijkstra_between(&data.routing_data, &routing::Endpoint::Edge(source_snap), &routing::Endpoint::Edge(target_snap), constraints, routing::cost_function(metric, tmc.is_some()), &tmc_state);
+
+	match route {
+		Some(mut route) => {
+			if let Some(tolerance) = simplify {
+				route.simplify(tolerance);
+			}
+			println!("{}", format::encode_route(&route, format, time::get_time()));
+			Ok(())
+		},
+		None => Err("no route found".to_string()),
+	}
+}
+
+fn run_batch(state: &str, input: &str, output: &str, config: &batch::BatchConfig) -> Result<(), String> {
+	let data = load_state(state, &data::StateHeader::new(&None, parser::profile_hash()))?;
+	batch::run(data, input, output, config)
+}
+
+fn run_info(state: &str) -> Result<(), String> {
+	let data = load_state(state, &data::StateHeader::new(&None, parser::profile_hash()))?;
+
+	println!("nodes: {}, edges: {}, tmc locations: {}", data.routing_data.internal_nodes.len(), data.routing_data.internal_edges.len(), data.routing_data.tmc_mapping.len());
+	for profile in data::PROFILES.iter() {
+		let nodes = data.routing_data.node_components.iter().filter(|flags| *flags & profile != 0).count();
+		let edges = data.routing_data.internal_edges.iter().filter(|edge| edge.constraints & profile != 0).count();
+		println!("profile {}: {} nodes, {} edges", profile, nodes, edges);
+	}
+	println!("edge index: {} items, {} levels", data.edge_index.len(), data.edge_index.levels());
+	println!("addresses: {}, names: {}", data.addresses.len(), data.names.len());
+	Ok(())
+}
+
+// the format is chosen by extension, the source is only compared if expected has one
+fn load_state(filename: &str, expected: &data::StateHeader) -> Result<data::State, String> {
+	if !is_file(filename) {
+		return Err("file not found".to_string());
+	}
+
+	if is_compressed(filename) {
+		read_from_disk(filename, expected)
+	} else {
+		read_flat_from_disk(filename, expected)
+	}
+}
+
+fn load_location_table(lcl: &Option<String>) -> Result<Option<lcl::LocationTable>, String> {
+	match *lcl {
+		Some(ref dir) => {
+			println!("Reading tmc location table from {}.. ", dir);
+			let table = lcl::LocationTable::read_dir(dir)?;
+			println!("Reading tmc location table from {}.. OK, {} points", dir, table.points.len());
+			Ok(Some(table))
+		},
+		None => Ok(None),
+	}
+}
+
+fn perform_parse(source: &OsString, mut header: data::StateHeader, output: &str, lcl: Option<&lcl::LocationTable>) -> data::State {
+	let data = parser::read_file(source, lcl);
+
+	header.build_time = time::get_time().sec;
+
+	if is_compressed(output) {
+		write_to_disk(output, &header, &data);
+	} else {
+		write_flat_to_disk(output, &header, &data);
+	}
 	return data;
 }
 
-fn write_to_disk(data: &data::State) {
-	println!("Writing state data to file {}.. ", STATE_FILE_NAME);
-	let writer = BufWriter::new(File::create(STATE_FILE_NAME).unwrap());
+fn is_compressed(filename: &str) -> bool {
+	filename.ends_with(".gz")
+}
+
+fn is_file(filename: &str) -> bool {
+	fs::metadata(filename).map(|metadata| metadata.is_file()).unwrap_or(false)
+}
+
+fn write_to_disk(filename: &str, header: &data::StateHeader, data: &data::State) {
+	println!("Writing state data to file {}.. ", filename);
+	let writer = BufWriter::new(File::create(filename).unwrap());
 	let mut encoder = ZlibEncoder::new(writer, Compression::Best);
-	encode_into(&data, &mut encoder, bincode::SizeLimit::Infinite).unwrap();
-	println!("Writing state data to file {}.. OK", STATE_FILE_NAME);
+	encode_into(header, &mut encoder, bincode::SizeLimit::Infinite).unwrap();
+	encode_into(data, &mut encoder, bincode::SizeLimit::Infinite).unwrap();
+	println!("Writing state data to file {}.. OK", filename);
 }
 
-fn read_from_disk() -> data::State {
-	println!("Reading state data from file {}.. ", STATE_FILE_NAME);
-	let reader = BufReader::new(File::open(STATE_FILE_NAME).unwrap());
+fn read_from_disk(filename: &str, expected: &data::StateHeader) -> Result<data::State, String> {
+	println!("Reading state data from file {}.. ", filename);
+	let reader = BufReader::new(File::open(filename).map_err(|e| e.to_string())?);
 	let mut decoder = ZlibDecoder::new(reader);
-	let decoded: data::State = decode_from(&mut decoder, bincode::SizeLimit::Infinite).unwrap();
-	println!("Reading state data from file {}.. OK", STATE_FILE_NAME);
-	return decoded;
+
+	// the header is checked before the (slow) decode of the actual data
+	let header: data::StateHeader = decode_from(&mut decoder, bincode::SizeLimit::Bounded(1 << 16)).map_err(|e| format!("invalid header: {}", e))?;
+	header.check(expected)?;
+	print_header(&header);
+
+	let decoded: data::State = decode_from(&mut decoder, bincode::SizeLimit::Infinite).map_err(|e| format!("invalid state data: {}", e))?;
+	println!("Reading state data from file {}.. OK", filename);
+	return Ok(decoded);
 }
 
-fn build_dummy_data() -> data::State {
-	parser::build_dummy_data()
-}
\ No newline at end of file
+fn write_flat_to_disk(filename: &str, header: &data::StateHeader, data: &data::State) {
+	println!("Writing flat state data to file {}.. ", filename);
+	flat::write_state(filename, header, data).unwrap();
+	println!("Writing flat state data to file {}.. OK", filename);
+}
+
+fn read_flat_from_disk(filename: &str, expected: &data::StateHeader) -> Result<data::State, String> {
+	println!("Mapping state data from file {}.. ", filename);
+	let (header, mapped) = flat::read_state(filename, expected).map_err(|e| e.to_string())?;
+	print_header(&header);
+	println!("Mapping state data from file {}.. OK", filename);
+	return Ok(mapped);
+}
+
+fn print_header(header: &data::StateHeader) {
+	println!("state version {}, built at {} from {} ({} bytes), profile {:016x}", header.version, time::at(time::Timespec::new(header.build_time, 0)).rfc3339(), header.source_name, header.source_size, header.profile_hash);
+}
@@ -0,0 +1,333 @@
+use std::f64;
+use rustc_serialize::json::Json;
+use time;
+
+// gps noise in meters (newson & krumm, "hidden markov map matching through noise and sparseness")
+const SIGMA: f64 = 4.07;
+// meters, how much the route distance may differ from the distance between two points
+const BETA: f64 = 10.0;
+// meters added to the transition search bound
+const MAX_DETOUR: f64 = 500.0;
+// m/s, bounds the transition search between timestamped points
+const MAX_SPEED: f64 = 60.0;
+
+#[derive(Debug, Clone)]
+pub struct TracePoint {
+	pub position: ::data::Position,
+	// unix seconds
+	pub time: Option<i64>,
+}
+
+pub struct MatchConfig {
+	pub constraints: u8,
+	// meters around each point in which candidates are searched
+	pub radius: f64,
+	pub candidates: usize,
+}
+
+impl MatchConfig {
+	pub fn new(constraints: u8) -> MatchConfig {
+		MatchConfig { constraints: constraints, radius: 50.0, candidates: 8 }
+	}
+}
+
+#[derive(Debug, Clone, RustcEncodable)]
+pub struct MatchedPoint {
+	// index into the trace
+	pub index: usize,
+	pub edge: usize,
+	pub position: [f64; 2],
+	pub distance: f64,
+	pub time: Option<i64>,
+}
+
+// a part of the trace which could be matched without a break
+#[derive(Debug, Clone, RustcEncodable)]
+pub struct Matching {
+	pub points: Vec<MatchedPoint>,
+	pub edges: Vec<usize>,
+	pub path: Vec<[f64; 2]>,
+	pub distance: f64,
+}
+
+#[derive(Debug, Clone, RustcEncodable)]
+pub struct MatchResult {
+	pub matchings: Vec<Matching>,
+	// trace indices without candidates
+	pub unmatched: Vec<usize>,
+}
+
+// viterbi state of the current unbroken part of the trace
+struct Segment {
+	points: Vec<usize>,
+	// [step][candidate] -> best candidate of the previous step
+	back: Vec<Vec<usize>>,
+	// [step][candidate] -> length and edges of the route from that candidate, kept from the transition search
+	routes: Vec<Vec<(f64, Vec<usize>)>>,
+	scores: Vec<f64>,
+}
+
+// trkpt elements with lat / lon attributes and an optional time child
+pub fn parse_gpx(gpx: &str) -> Result<Vec<TracePoint>, String> {
+	let mut trace = Vec::new();
+
+	for element in gpx.split("<trkpt").skip(1) {
+		let element = match element.find("</trkpt>") {
+			Some(end) => &element[..end],
+			None => element,
+		};
+		let tag_end = element.find('>').ok_or("unterminated trkpt".to_string())?;
+		let tag = &element[..tag_end];
+
+		let lat = attribute(tag, "lat").and_then(|lat| lat.parse::<f64>().ok());
+		let lon = attribute(tag, "lon").and_then(|lon| lon.parse::<f64>().ok());
+
+		let time = match (element.find("<time>"), element.find("</time>")) {
+			(Some(start), Some(end)) if start < end => parse_time(&element[start + 6..end]),
+			_ => None,
+		};
+
+		match (lat, lon) {
+			(Some(lat), Some(lon)) => trace.push(TracePoint { position: ::data::Position { lat: lat, lon: lon }, time: time }),
+			_ => return Err(format!("invalid trkpt: {}", tag.trim())),
+		}
+	}
+
+	Ok(trace)
+}
+
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+	for quote in &["\"", "'"] {
+		let pattern = format!(" {}={}", name, quote);
+		if let Some(start) = tag.find(&pattern) {
+			let value = &tag[start + pattern.len()..];
+			return value.find(quote).map(|end| &value[..end]);
+		}
+	}
+	None
+}
+
+// rfc3339, fractional seconds are ignored. times with an offset like +02:00 are converted to utc,
+// times without any zone are taken as utc
+fn parse_time(raw: &str) -> Option<i64> {
+	let raw = raw.trim();
+	if raw.len() < 19 || !raw.is_char_boundary(19) {
+		return None;
+	}
+	let local = time::strptime(&raw[..19], "%Y-%m-%dT%H:%M:%S").ok()?.to_timespec().sec;
+
+	let zone = raw[19..].trim_start_matches(|c: char| c == '.' || c.is_ascii_digit());
+	let offset = match zone {
+		"" | "Z" | "z" => 0,
+		_ if zone.len() == 6 && zone.is_ascii() && (zone.starts_with('+') || zone.starts_with('-')) && &zone[3..4] == ":" => {
+			let hours = zone[1..3].parse::<i64>().ok()?;
+			let minutes = zone[4..6].parse::<i64>().ok()?;
+			let offset = hours * 3600 + minutes * 60;
+			if zone.starts_with('-') { -offset } else { offset }
+		},
+		_ => return None,
+	};
+	Some(local - offset)
+}
+
+// [{"lat": .., "lon": .., "time": ..}, ..], time is optional and either unix seconds or rfc3339
+pub fn parse_json(raw: &str) -> Result<Vec<TracePoint>, String> {
+	let points = match Json::from_str(raw) {
+		Ok(Json::Array(points)) => points,
+		Ok(_) => return Err("expected a list of points".to_string()),
+		Err(e) => return Err(e.to_string()),
+	};
+
+	points.iter().enumerate().map(|(i, point)| {
+		let lat = point.find("lat").and_then(|lat| lat.as_f64());
+		let lon = point.find("lon").and_then(|lon| lon.as_f64());
+		let time = match point.find("time") {
+			Some(&Json::String(ref time)) => parse_time(time),
+			Some(time) => time.as_f64().map(|time| time as i64),
+			None => None,
+		};
+
+		match (lat, lon) {
+			(Some(lat), Some(lon)) => Ok(TracePoint { position: ::data::Position { lat: lat, lon: lon }, time: time }),
+			_ => Err(format!("point {} has no lat / lon", i)),
+		}
+	}).collect()
+}
+
+fn emission(snap: &::data::Snap) -> f64 {
+	-0.5 * (snap.distance / SIGMA).powi(2)
+}
+
+// hidden markov model over the candidate edges of every point, the most likely sequence is found with viterbi
+// a point without reachable candidates breaks the trace into separate matchings
+pub fn match_trace(data: &::data::State, trace: &[TracePoint], config: &MatchConfig) -> MatchResult {
+	// candidates are directed, the hmm state includes the driving direction
+	let query = ::data::SnapQuery { constraints: config.constraints, radius: Some(config.radius), bearing: None };
+	let candidates: Vec<Vec<::data::Snap>> = trace.iter().map(|point| {
+		data.find_closest_edges(&point.position, &query, config.candidates).into_iter().map(|snap| ::data::Snap { directed: true, ..snap }).collect()
+	}).collect();
+
+	let mut result = MatchResult { matchings: Vec::new(), unmatched: Vec::new() };
+	let mut current: Option<Segment> = None;
+
+	for (t, point_candidates) in candidates.iter().enumerate() {
+		if point_candidates.is_empty() {
+			result.unmatched.push(t);
+			continue;
+		}
+
+		let emissions: Vec<f64> = point_candidates.iter().map(emission).collect();
+		let targets: Vec<::routing::Endpoint> = point_candidates.iter().map(|snap| ::routing::Endpoint::Edge(*snap)).collect();
+
+		let mut extended = false;
+		if let Some(ref mut segment) = current {
+			let prev = *segment.points.last().unwrap();
+			let straight = trace[prev].position.distance(&trace[t].position);
+			let bound = match (trace[prev].time, trace[t].time) {
+				(Some(prev_time), Some(time)) if time > prev_time => straight.max((time - prev_time) as f64 * MAX_SPEED),
+				_ => straight * 2.0,
+			} + MAX_DETOUR;
+
+			let mut scores = vec![f64::NEG_INFINITY; point_candidates.len()];
+			let mut back = vec![0; point_candidates.len()];
+			let mut routes = vec![(0.0, Vec::new()); point_candidates.len()];
+			let usable = |edge: usize| data.routing_data.internal_edges[edge].constraints & config.constraints != 0;
+
+			for (i, prev_snap) in candidates[prev].iter().enumerate() {
+				if segment.scores[i] == f64::NEG_INFINITY {
+					continue;
+				}
+
+				let search = ::routing::bounded_search(&data.routing_data, &::routing::Endpoint::Edge(*prev_snap), &targets, &usable, bound);
+				for j in 0..targets.len() {
+					if let Some(distance) = search.distance(j) {
+						let score = segment.scores[i] + emissions[j] - (distance - straight).abs() / BETA;
+						if score > scores[j] {
+							scores[j] = score;
+							back[j] = i;
+							routes[j] = (distance, search.edges(j).unwrap_or(Vec::new()));
+						}
+					}
+				}
+			}
+
+			if scores.iter().any(|score| *score > f64::NEG_INFINITY) {
+				segment.points.push(t);
+				segment.back.push(back);
+				segment.routes.push(routes);
+				segment.scores = scores;
+				extended = true;
+			}
+		}
+
+		if !extended {
+			if let Some(segment) = current.take() {
+				result.matchings.push(build_matching(data, trace, &candidates, &segment));
+			}
+			current = Some(Segment { points: vec![t], back: vec![Vec::new()], routes: vec![Vec::new()], scores: emissions });
+		}
+	}
+
+	if let Some(segment) = current.take() {
+		result.matchings.push(build_matching(data, trace, &candidates, &segment));
+	}
+
+	result
+}
+
+// backtracks the best candidate sequence and connects the candidates with their shortest routes
+fn build_matching(data: &::data::State, trace: &[TracePoint], candidates: &[Vec<::data::Snap>], segment: &Segment) -> Matching {
+	let mut best = (0..segment.scores.len()).fold(0, |best, i| if segment.scores[i] > segment.scores[best] { i } else { best });
+
+	let mut chosen = vec![0; segment.points.len()];
+	for step in (0..segment.points.len()).rev() {
+		chosen[step] = best;
+		if step > 0 {
+			best = segment.back[step][best];
+		}
+	}
+
+	let snaps: Vec<::data::Snap> = segment.points.iter().zip(chosen.iter()).map(|(&t, &c)| candidates[t][c]).collect();
+
+	let mut matching = Matching { points: Vec::new(), edges: vec![snaps[0].edge], path: vec![[snaps[0].position.lat, snaps[0].position.lon]], distance: 0.0 };
+
+	for (&t, snap) in segment.points.iter().zip(snaps.iter()) {
+		matching.points.push(MatchedPoint { index: t, edge: snap.edge, position: [snap.position.lat, snap.position.lon], distance: snap.distance, time: trace[t].time });
+	}
+
+	// the routes between the chosen candidates were found by the transition search
+	for (step, pair) in snaps.windows(2).enumerate() {
+		let (distance, ref edges) = segment.routes[step + 1][chosen[step + 1]];
+		matching.distance += distance;
+
+		let mut path = vec![[pair[0].position.lat, pair[0].position.lon]];
+		for &edge in edges.iter().take(edges.len().saturating_sub(1)) {
+			let position = data.routing_data.position(data.routing_data.internal_edges[edge].target as usize);
+			path.push([position.lat, position.lon]);
+		}
+		path.push([pair[1].position.lat, pair[1].position.lon]);
+
+		for &edge in edges {
+			if matching.edges.last() != Some(&edge) {
+				matching.edges.push(edge);
+			}
+		}
+		for point in path {
+			if matching.path.last() != Some(&point) {
+				matching.path.push(point);
+			}
+		}
+	}
+
+	matching
+}
+
+#[test]
+fn test_parse_traces() {
+	let gpx = "<gpx><trk><trkseg>
+		<trkpt lat=\"48.1\" lon=\"9.1\"><time>2016-07-01T10:00:00Z</time></trkpt>
+		<trkpt lon='9.2' lat='48.2'/>
+	</trkseg></trk></gpx>";
+	let trace = parse_gpx(gpx).unwrap();
+	assert_eq!(trace.len(), 2);
+	assert_eq!(trace[0].time, Some(1467367200));
+	assert_eq!(trace[1].position.lon, 9.2);
+	assert_eq!(trace[1].time, None);
+
+	let trace = parse_json("[{\"lat\": 48.1, \"lon\": 9.1, \"time\": 1467367200}, {\"lat\": 48.2, \"lon\": 9.2, \"time\": \"2016-07-01T10:00:05.5Z\"}]").unwrap();
+	assert_eq!(trace[1].time, Some(1467367205));
+
+	// local times with an offset are converted to utc, unknown zones are dropped
+	assert_eq!(parse_time("2016-07-01T12:00:00+02:00"), Some(1467367200));
+	assert_eq!(parse_time("2016-07-01T08:30:00.250-01:30"), Some(1467367200));
+	assert_eq!(parse_time("2016-07-01T10:00:00"), Some(1467367200));
+	assert_eq!((parse_time("2016-07-01T10:00:00 CEST"), parse_time("2016-07-01T10:00:00+2")), (None, None));
+
+	assert!(parse_json("[{\"lat\": 48.1}]").is_err());
+}
+
+#[test]
+fn test_match_trace() {
+	// an L shaped road, ~110 m per segment
+	let road: Vec<::data::Position> = vec![(48.0, 9.0), (48.0, 9.0015), (48.0, 9.003), (48.001, 9.003), (48.002, 9.003)].into_iter().map(|(lat, lon)| ::data::Position { lat: lat, lon: lon }).collect();
+	let data = ::parser::build_road_data(&road);
+
+	let trace: Vec<TracePoint> = vec![(48.00003, 9.0005), (47.99996, 9.002), (48.0005, 9.00296), (48.0015, 9.00303), (48.1, 9.1)].into_iter()
+		.map(|(lat, lon)| TracePoint { position: ::data::Position { lat: lat, lon: lon }, time: None }).collect();
+
+	let result = match_trace(&data, &trace, &MatchConfig::new(::data::FLAG_CAR));
+	assert_eq!(result.unmatched, vec![4]);
+	assert_eq!(result.matchings.len(), 1);
+
+	let matching = &result.matchings[0];
+	assert_eq!(matching.points.len(), 4);
+	assert!(matching.points.iter().all(|point| point.distance < 10.0));
+
+	// every matched edge leads away from the start of the road
+	let node_ids: Vec<(i64, i64)> = matching.edges.iter().map(|&edge| {
+		let ref edge = data.routing_data.internal_edges[edge];
+		(data.routing_data.internal_nodes[edge.source as usize], data.routing_data.internal_nodes[edge.target as usize])
+	}).collect();
+	assert_eq!(node_ids, vec![(1, 2), (2, 3), (3, 4), (4, 5)]);
+	assert!((matching.distance - trace[0].position.distance(&road[1]) - road[1].distance(&road[2]) - road[2].distance(&trace[3].position)).abs() < 5.0);
+}
@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+// tile coordinate range of the mapbox vector tile spec
+pub const EXTENT: u32 = 4096;
+// lower zoom levels would contain too many edges, their tiles are left empty
+pub const MIN_ZOOM: u32 = 12;
+// extra space around a tile in tile coordinates, so lines aren't cut at the tile border
+const BUFFER: f64 = 64.0;
+
+const GEOM_LINESTRING: u64 = 2;
+const CMD_MOVE_TO: u32 = 1;
+const CMD_LINE_TO: u32 = 2;
+
+// minimal protobuf writer, only the wire types used by the vector tile spec
+struct ProtoWriter {
+	buffer: Vec<u8>,
+}
+
+impl ProtoWriter {
+	fn new() -> ProtoWriter {
+		ProtoWriter { buffer: Vec::new() }
+	}
+
+	fn varint(&mut self, mut value: u64) {
+		while value >= 0x80 {
+			self.buffer.push((value as u8 & 0x7f) | 0x80);
+			value >>= 7;
+		}
+		self.buffer.push(value as u8);
+	}
+
+	fn key(&mut self, field: u32, wire_type: u32) {
+		self.varint(((field << 3) | wire_type) as u64);
+	}
+
+	fn uint(&mut self, field: u32, value: u64) {
+		self.key(field, 0);
+		self.varint(value);
+	}
+
+	fn double(&mut self, field: u32, value: f64) {
+		self.key(field, 1);
+		self.buffer.extend_from_slice(&value.to_bits().to_le_bytes());
+	}
+
+	fn bytes(&mut self, field: u32, value: &[u8]) {
+		self.key(field, 2);
+		self.varint(value.len() as u64);
+		self.buffer.extend_from_slice(value);
+	}
+
+	fn packed(&mut self, field: u32, values: &[u32]) {
+		let mut inner = ProtoWriter::new();
+		for value in values {
+			inner.varint(*value as u64);
+		}
+		self.bytes(field, &inner.buffer);
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+	Uint(u64),
+	Double(f64),
+	Bool(bool),
+	String(String),
+}
+
+impl Value {
+	fn encode(&self) -> Vec<u8> {
+		let mut writer = ProtoWriter::new();
+		match *self {
+			Value::Double(value) => writer.double(3, value),
+			Value::Uint(value) => writer.uint(5, value),
+			Value::Bool(value) => writer.uint(7, value as u64),
+			Value::String(ref value) => writer.bytes(1, value.as_bytes()),
+		}
+		writer.buffer
+	}
+}
+
+// collects features of one layer, keys and values are deduplicated into the layer tables
+struct Layer {
+	name: String,
+	keys: Vec<String>,
+	values: Vec<Value>,
+	features: Vec<Vec<u8>>,
+}
+
+impl Layer {
+	fn new(name: &str) -> Layer {
+		Layer { name: name.to_string(), keys: Vec::new(), values: Vec::new(), features: Vec::new() }
+	}
+
+	fn tag(&mut self, key: &str, value: Value) -> [u32; 2] {
+		let key_index = match self.keys.iter().position(|k| k == key) {
+			Some(index) => index,
+			None => { self.keys.push(key.to_string()); self.keys.len() - 1 }
+		};
+		let value_index = match self.values.iter().position(|v| *v == value) {
+			Some(index) => index,
+			None => { self.values.push(value); self.values.len() - 1 }
+		};
+		[key_index as u32, value_index as u32]
+	}
+
+	// lines shorter than a tile unit collapse to a single point and are skipped
+	fn add_line(&mut self, id: u64, points: &[(i32, i32)], properties: Vec<(&str, Value)>) {
+		let geometry = match line_geometry(points) {
+			Some(geometry) => geometry,
+			None => return,
+		};
+
+		let mut tags = Vec::new();
+		for (key, value) in properties {
+			tags.extend_from_slice(&self.tag(key, value));
+		}
+
+		let mut feature = ProtoWriter::new();
+		feature.uint(1, id);
+		feature.packed(2, &tags);
+		feature.uint(3, GEOM_LINESTRING);
+		feature.packed(4, &geometry);
+		self.features.push(feature.buffer);
+	}
+
+	fn encode(&self) -> Vec<u8> {
+		let mut layer = ProtoWriter::new();
+		layer.uint(15, 2);
+		layer.bytes(1, self.name.as_bytes());
+		for feature in &self.features {
+			layer.bytes(2, feature);
+		}
+		for key in &self.keys {
+			layer.bytes(3, key.as_bytes());
+		}
+		for value in &self.values {
+			layer.bytes(4, &value.encode());
+		}
+		layer.uint(5, EXTENT as u64);
+		layer.buffer
+	}
+}
+
+fn zigzag(value: i32) -> u32 {
+	((value << 1) ^ (value >> 31)) as u32
+}
+
+fn command(id: u32, count: u32) -> u32 {
+	(id & 0x7) | (count << 3)
+}
+
+// one move to the first point and line tos to the others, all relative to the previous point. repeated points
+// are dropped as line tos of length zero are invalid, None if less than two distinct points remain
+fn line_geometry(points: &[(i32, i32)]) -> Option<Vec<u32>> {
+	let mut points = points.to_vec();
+	points.dedup();
+	if points.len() < 2 {
+		return None;
+	}
+
+	let mut geometry = vec![command(CMD_MOVE_TO, 1), zigzag(points[0].0), zigzag(points[0].1), command(CMD_LINE_TO, points.len() as u32 - 1)];
+
+	for pair in points.windows(2) {
+		geometry.push(zigzag(pair[1].0 - pair[0].0));
+		geometry.push(zigzag(pair[1].1 - pair[0].1));
+	}
+	Some(geometry)
+}
+
+// web mercator position in tiles of zoom level z
+fn tile_position(position: &::data::Position, z: u32) -> (f64, f64) {
+	let n = (1u64 << z) as f64;
+	let lat = position.lat.to_radians();
+
+	let x = (position.lon + 180.0) / 360.0 * n;
+	let y = (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / PI) / 2.0 * n;
+	(x, y)
+}
+
+// north west corner of a tile
+fn tile_corner(z: u32, x: u32, y: u32) -> ::data::Position {
+	let n = (1u64 << z) as f64;
+	let lon = x as f64 / n * 360.0 - 180.0;
+	let lat = (PI * (1.0 - 2.0 * y as f64 / n)).sinh().atan().to_degrees();
+	::data::Position { lat: lat, lon: lon }
+}
+
+pub fn valid_tile(z: u32, x: u32, y: u32) -> bool {
+	z <= 24 && (x as u64) < (1u64 << z) && (y as u64) < (1u64 << z)
+}
+
+// a single "edges" layer with one line per edge intersecting the tile
+pub fn encode_edge_tile(data: &::data::State, edge_tmc: &HashMap<usize, Vec<::data::TMCLocation>>, tmc_state: &::data::TMCState, z: u32, x: u32, y: u32) -> Vec<u8> {
+	let mut layer = Layer::new("edges");
+
+	if z >= MIN_ZOOM {
+		let buffer = BUFFER / EXTENT as f64;
+		let north_west = tile_corner(z, x, y);
+		let south_east = tile_corner(z, x + 1, y + 1);
+		let (lat_buffer, lon_buffer) = ((north_west.lat - south_east.lat) * buffer, (south_east.lon - north_west.lon) * buffer);
+
+		let bbox = ::rtree::Rect::from_positions(&::data::Position { lat: south_east.lat - lat_buffer, lon: north_west.lon - lon_buffer }, &::data::Position { lat: north_west.lat + lat_buffer, lon: south_east.lon + lon_buffer });
+
+		let mut edges = data.edge_index.search(&bbox);
+		edges.sort();
+
+		let to_tile = |position: &::data::Position| {
+			let (px, py) = tile_position(position, z);
+			(((px - x as f64) * EXTENT as f64).round() as i32, ((py - y as f64) * EXTENT as f64).round() as i32)
+		};
+
+		for edge_id in edges {
+			let ref edge = data.routing_data.internal_edges[edge_id as usize];
+			let points = [to_tile(&data.routing_data.position(edge.source as usize)), to_tile(&data.routing_data.position(edge.target as usize))];
+
+			let mut properties = vec![
+				("speed", Value::Uint((edge.speed as f64 * 3.6).round() as u64)),
+				("constraints", Value::Uint(edge.constraints as u64)),
+				("tmc", Value::Bool(edge_tmc.contains_key(&(edge_id as usize)))),
+			];
+			// cid:tabcd:loc of the mapped locations, comma separated
+			if let Some(locations) = edge_tmc.get(&(edge_id as usize)) {
+				properties.push(("tmc_locations", Value::String(locations.iter().map(|location| location.to_string()).collect::<Vec<_>>().join(","))));
+			}
+			// the slowdown for cars, tmc events mostly concern roads without bike and foot traffic
+			if let Some(slowdown) = tmc_state.current_edge_events.get(&(edge_id as usize)) {
+				properties.push(("slowdown", Value::Double(slowdown[::data::profile_index(::data::FLAG_CAR)])));
+			}
+
+			layer.add_line(edge_id as u64, &points, properties);
+		}
+	}
+
+	let mut tile = ProtoWriter::new();
+	tile.bytes(3, &layer.encode());
+	tile.buffer
+}
+
+#[test]
+fn test_encoding() {
+	let mut writer = ProtoWriter::new();
+	writer.varint(300);
+	assert_eq!(writer.buffer, vec![0xac, 0x02]);
+
+	assert_eq!(zigzag(-1), 1);
+	assert_eq!(zigzag(1), 2);
+
+	// example from the vector tile spec: LineString (2,2) (2,10) (10,10)
+	assert_eq!(line_geometry(&[(2, 2), (2, 10), (10, 10)]), Some(vec![9, 4, 4, 18, 0, 16, 16, 0]));
+	assert_eq!(line_geometry(&[(2, 2), (2, 2), (2, 10)]), Some(vec![9, 4, 4, 10, 0, 16]));
+
+	// an edge shorter than a tile unit isn't added to the layer
+	let positions = [::data::Position { lat: 48.0, lon: 9.0 }, ::data::Position { lat: 48.0, lon: 9.000001 }];
+	assert!(positions[0].distance(&positions[1]) < 0.1);
+	let points = [tile_position(&positions[0], MIN_ZOOM), tile_position(&positions[1], MIN_ZOOM)];
+	let points: Vec<(i32, i32)> = points.iter().map(|&(px, py)| ((px.fract() * EXTENT as f64).round() as i32, (py.fract() * EXTENT as f64).round() as i32)).collect();
+	let mut layer = Layer::new("edges");
+	layer.add_line(1, &points, vec![("speed", Value::Uint(50))]);
+	assert!(layer.features.is_empty() && layer.keys.is_empty());
+
+	let corner = tile_corner(1, 1, 1);
+	assert!(corner.lat.abs() < 1e-9 && corner.lon.abs() < 1e-9);
+	let (tx, ty) = tile_position(&tile_corner(14, 8581, 5665), 14);
+	assert!((tx - 8581.0).abs() < 1e-6 && (ty - 5665.0).abs() < 1e-6);
+}
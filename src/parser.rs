@@ -5,6 +5,8 @@ use std::ffi::OsString;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::f64;
+use std::cmp;
+use std::mem;
 
 use osmpbfreader::OsmObj;
 use osmpbfreader::OsmPbfReader;
@@ -16,7 +18,11 @@ struct ParsedEdge {
 	length: f64,
 	constraints: u8,
 	speed: f64,
-	tmc_id: Vec<u32>
+	tmc_id: Vec<::data::TMCLocation>,
+	// string ids of the way's name and ref
+	name: u32,
+	road_ref: u32,
+	class: u8,
 }
 
 struct ParseData {
@@ -29,26 +35,56 @@ struct ParseData {
 	// edges
 	edges: Vec<ParsedEdge>,
 	// tmc next id
-	tmc_next: HashMap<(u32, bool), u32>
+	tmc_next: HashMap<(::data::TMCLocation, bool), ::data::TMCLocation>,
+	// way id -> string ids of name and ref of the filtered ways
+	way_names: HashMap<i64, (u32, u32)>,
+	// way id -> road class of the filtered ways
+	way_classes: HashMap<i64, u8>,
+	names: ::strings::StringTableBuilder,
+	addresses: Vec<::data::Address>,
+	// nodes of buildings with an address, housenumber and street
+	address_ways: Vec<(Vec<i64>, u32, u32)>,
+	// node ids of the address ways and their positions
+	address_nodes_used: HashSet<i64>,
+	address_nodes: HashMap<i64, ::data::Position>,
+}
+
+impl ParseData {
+	fn new() -> ParseData {
+		ParseData {
+			nodes_used: HashSet::new(),
+			filtered_ways: HashMap::new(),
+			nodes: HashMap::new(),
+			edges: Vec::new(),
+			tmc_next: HashMap::new(),
+			way_names: HashMap::new(),
+			way_classes: HashMap::new(),
+			names: ::strings::StringTableBuilder::new(),
+			addresses: Vec::new(),
+			address_ways: Vec::new(),
+			address_nodes_used: HashSet::new(),
+			address_nodes: HashMap::new(),
+		}
+	}
 }
 
 #[derive(Debug, Hash, Eq, PartialEq)]
 struct TMCInfo {
-	id: u32,
+	id: ::data::TMCLocation,
 	next: Option<TMCNext>
 }
 
 #[derive(Debug, Hash, Eq, PartialEq)]
 struct TMCNext {
 	direction: bool,
-	next: u32
+	next: ::data::TMCLocation
 }
 
 #[derive(Debug, Clone)]
 struct WayConstraints {
 	access: u8,
 	speed: f64,
-	tmc_id: Vec<u32>,
+	tmc_id: Vec<::data::TMCLocation>,
 }
 
 struct WayDefaults {
@@ -61,10 +97,11 @@ enum OneWay {
 	REVERSE
 }
 
-pub fn read_file(filename: &OsString) -> ::data::State {
+// the location table, if given, completes the tmc locations of the osm tags
+pub fn read_file(filename: &OsString, lcl: Option<&::lcl::LocationTable>) -> ::data::State {
 	println!("will parse file: {:?}", &filename);
 
-	let mut parse_result = ParseData { nodes_used: HashSet::new(), filtered_ways: HashMap::new(), nodes: HashMap::new(), edges: Vec::new(), tmc_next: HashMap::new() };
+	let mut parse_result = ParseData::new();
 
 	let start_p1 = PreciseTime::now();
 	first_parse(&filename, &mut parse_result);
@@ -86,6 +123,7 @@ pub fn read_file(filename: &OsString) -> ::data::State {
 	println!("P2 | nodes_used: {}", parse_result.nodes_used.len());
 	println!("P2 | edges:      {}", parse_result.edges.len());
 	println!("P2 | nodes:      {}", parse_result.nodes.len());
+	println!("P2 | addresses:  {}", parse_result.addresses.len());
 	println!("P2 | duration:   {}", start_p2.to(end_p2));
 
 	let start_p3 = PreciseTime::now();
@@ -98,47 +136,134 @@ pub fn read_file(filename: &OsString) -> ::data::State {
 	println!("P3 | nodes:      {}", parse_result.nodes.len());
 	println!("P3 | duration:   {}", start_p3.to(end_p3));
 
+	let names = mem::replace(&mut parse_result.names, ::strings::StringTableBuilder::new()).table;
+	let addresses = mem::replace(&mut parse_result.addresses, Vec::new());
+
 	let start_b = PreciseTime::now();
-	let routing_data = build_routing_data(parse_result);
+	let mut routing_data = build_routing_data(parse_result);
 	let end_b = PreciseTime::now();
 
 	println!("B  | edges:     {}", routing_data.internal_edges.len());
 	println!("B  | nodes:     {}", routing_data.internal_nodes.len());
 	println!("B  | offset:    {}", routing_data.internal_offset.len());
-	println!("B  | osm_nodes: {}", routing_data.osm_nodes.len());
+	println!("B  | positions: {}", routing_data.node_positions.len());
 	println!("B  | duration:  {}", start_b.to(end_b));
 
-	let start_g = PreciseTime::now();
-	let grid = build_grid(&routing_data);
-	let end_g = PreciseTime::now();
+	let start_c = PreciseTime::now();
+	filter_components(&mut routing_data);
+	let end_c = PreciseTime::now();
+
+	println!("C  | duration:  {}", start_c.to(end_c));
+
+	let start_i = PreciseTime::now();
+	let edge_index = build_edge_index(&routing_data);
+	let end_i = PreciseTime::now();
+
+	println!("I  | items:     {}", edge_index.len());
+	println!("I  | levels:    {}", edge_index.levels());
+	println!("I  | duration:  {}", start_i.to(end_i));
+
+	let start_a = PreciseTime::now();
+	let address_index = build_address_index(&addresses);
+	let end_a = PreciseTime::now();
+
+	println!("A  | addresses: {}", address_index.len());
+	println!("A  | names:     {}", names.len());
+	println!("A  | duration:  {}", start_a.to(end_a));
+
+	let mut state = ::data::State { routing_data: routing_data, edge_index: edge_index, names: names, addresses: addresses.into(), address_index: address_index };
 
-	println!("G  | bounds:      {:?}", grid.bbox);
-	println!("G  | bin_cnt_lat: {}", grid.bin_count_lat);
-	println!("G  | bin_cnt_lon: {}", grid.bin_count_lon);
-	println!("G  | duration:    {}", start_g.to(end_g));
+	if let Some(table) = lcl {
+		let start_l = PreciseTime::now();
+		::lcl::apply(table, &mut state);
+		let end_l = PreciseTime::now();
 
-	return ::data::State { routing_data: routing_data, grid: grid };
+		println!("L  | duration:  {}", start_l.to(end_l));
+	}
+
+	return state;
 }
 
 #[test]
-#[ignore]
 fn test_routing_data_gen() {
-	let routing_data = build_dummy_data();
+	let routing_data = build_dummy_routing_data();
+
+	assert_eq!(routing_data.internal_nodes.to_vec(), vec![5000, 5001, 5002, 5003, 5004]);
 
-	println!("NODES: {:?}", routing_data.internal_nodes);
-	println!("EDGES: {:?}", routing_data.internal_edges);
-	println!("OFFSET: {:?}", routing_data.internal_offset);
+	// edges are grouped by source node, each node's edges start at its offset
+	let edges: Vec<(u32, u32, f64)> = routing_data.internal_edges.iter().map(|edge| (edge.source as u32, edge.target as u32, edge.length as f64)).collect();
+	assert_eq!(edges, vec![(0, 2, 10.0), (0, 1, 1.0), (2, 3, 1000.0), (2, 1, 100.0), (3, 4, 100000.0), (3, 0, 10000.0)]);
+	assert_eq!(routing_data.internal_offset.to_vec(), vec![0, 2, 2, 4, 6]);
+	let counts: Vec<usize> = (0..5).map(|node| routing_data.edge_range(node)).map(|(start, end)| end - start).collect();
+	assert_eq!(counts, vec![2, 0, 2, 2, 0]);
+}
+
+#[test]
+fn test_filter_components() {
+	let routing_data = build_dummy_routing_data();
 
-	assert_eq!(routing_data.internal_offset, vec![0, 2, 2, 4, 5]);
+	// 5000 -> 5002 -> 5003 -> 5000 is the only cycle, 5001 and 5004 are dead ends
+	assert_eq!(routing_data.node_components.to_vec(), vec![::data::FLAG_CAR, 0, ::data::FLAG_CAR, ::data::FLAG_CAR, 0]);
+	assert_eq!(routing_data.internal_edges.iter().filter(|e| e.constraints & ::data::FLAG_CAR != 0).count(), 3);
 }
 
+#[test]
+fn test_parse_tmc_info() {
+	let german = |loc: u32| ::data::TMCLocation::new(::data::DEFAULT_TMC_TABLE, loc);
+	let info = parse_tmc_info(&"DE:12345+12346;DE:200".to_string(), None);
+	assert!(info.contains(&TMCInfo { id: german(12345), next: Some(TMCNext { direction: true, next: german(12346) }) }));
+	assert!(info.contains(&TMCInfo { id: german(200), next: None }));
+
+	// the table tags of the way override the country prefix, unknown countries are skipped
+	let table = ::data::TMCTable { cid: 40, tabcd: 3 };
+	let info = parse_tmc_info(&"DE:100-99".to_string(), Some(table));
+	assert!(info.contains(&TMCInfo { id: ::data::TMCLocation::new(table, 100), next: Some(TMCNext { direction: false, next: ::data::TMCLocation::new(table, 99) }) }));
+	assert!(parse_tmc_info(&"XX:100".to_string(), None).is_empty());
+	assert!(parse_tmc_info(&"CH:100".to_string(), None).contains(&TMCInfo { id: ::data::TMCLocation::new(::data::TMCTable { cid: 9, tabcd: 1 }, 100), next: None }));
+}
+
+#[cfg(test)]
 pub fn build_dummy_data() -> ::data::State {
-	let edge_vec = vec![ParsedEdge{id_from: 5000, id_to: 5001, length: 1.0, constraints: ::data::FLAG_CAR, speed: 13.89, tmc_id: Vec::new()},
-                        ParsedEdge{id_from: 5000, id_to: 5002, length: 10.0, constraints: ::data::FLAG_CAR, speed: 13.89, tmc_id: Vec::new()},
-                        ParsedEdge{id_from: 5002, id_to: 5001, length: 100.0, constraints: ::data::FLAG_CAR, speed: 13.89, tmc_id: Vec::new()},
-                        ParsedEdge{id_from: 5002, id_to: 5003, length: 1000.0, constraints: ::data::FLAG_CAR, speed: 13.89, tmc_id: Vec::new()},
-                        ParsedEdge{id_from: 5003, id_to: 5000, length: 10000.0, constraints: ::data::FLAG_CAR, speed: 13.89, tmc_id: Vec::new()},
-                        ParsedEdge{id_from: 5003, id_to: 5004, length: 100000.0, constraints: ::data::FLAG_CAR, speed: 13.89, tmc_id: Vec::new()},
+	let routing_data = build_dummy_routing_data();
+	let edge_index = build_edge_index(&routing_data);
+	::data::State { routing_data: routing_data, edge_index: edge_index, names: ::strings::StringTable::new(), addresses: ::flat::FlatVec::new(), address_index: build_address_index(&[]) }
+}
+
+// a two way road through the given positions, node ids start at 1
+#[cfg(test)]
+pub fn build_road_data(positions: &[::data::Position]) -> ::data::State {
+	let roads: Vec<(usize, usize, u8)> = (1..positions.len()).map(|i| (i - 1, i, ::data::FLAG_CAR)).collect();
+	build_graph_data(positions, &roads)
+}
+
+// two way roads between the positions with the given index and constraints, node ids start at 1
+#[cfg(test)]
+pub fn build_graph_data(positions: &[::data::Position], roads: &[(usize, usize, u8)]) -> ::data::State {
+	let nodes_map: HashMap<i64, ::data::Position> = positions.iter().enumerate().map(|(i, position)| (i as i64 + 1, *position)).collect();
+	let mut edge_vec = Vec::new();
+
+	for &(from, to, constraints) in roads {
+		let length = positions[from].distance(&positions[to]);
+		edge_vec.push(ParsedEdge{id_from: from as i64 + 1, id_to: to as i64 + 1, length: length, constraints: constraints, speed: 13.89, tmc_id: Vec::new(), name: ::strings::NONE, road_ref: ::strings::NONE, class: ::data::CLASS_OTHER});
+		edge_vec.push(ParsedEdge{id_from: to as i64 + 1, id_to: from as i64 + 1, length: length, constraints: constraints, speed: 13.89, tmc_id: Vec::new(), name: ::strings::NONE, road_ref: ::strings::NONE, class: ::data::CLASS_OTHER});
+	}
+
+	let parse_result = ParseData { nodes: nodes_map, edges: edge_vec, ..ParseData::new() };
+
+	let mut routing_data = build_routing_data(parse_result);
+	filter_components(&mut routing_data);
+	let edge_index = build_edge_index(&routing_data);
+	::data::State { routing_data: routing_data, edge_index: edge_index, names: ::strings::StringTable::new(), addresses: ::flat::FlatVec::new(), address_index: build_address_index(&[]) }
+}
+
+#[cfg(test)]
+pub fn build_dummy_routing_data() -> ::data::RoutingData {
+	let edge_vec = vec![ParsedEdge{id_from: 5000, id_to: 5001, length: 1.0, constraints: ::data::FLAG_CAR, speed: 13.89, tmc_id: Vec::new(), name: ::strings::NONE, road_ref: ::strings::NONE, class: ::data::CLASS_OTHER},
+                        ParsedEdge{id_from: 5000, id_to: 5002, length: 10.0, constraints: ::data::FLAG_CAR, speed: 13.89, tmc_id: Vec::new(), name: ::strings::NONE, road_ref: ::strings::NONE, class: ::data::CLASS_OTHER},
+                        ParsedEdge{id_from: 5002, id_to: 5001, length: 100.0, constraints: ::data::FLAG_CAR, speed: 13.89, tmc_id: Vec::new(), name: ::strings::NONE, road_ref: ::strings::NONE, class: ::data::CLASS_OTHER},
+                        ParsedEdge{id_from: 5002, id_to: 5003, length: 1000.0, constraints: ::data::FLAG_CAR, speed: 13.89, tmc_id: Vec::new(), name: ::strings::NONE, road_ref: ::strings::NONE, class: ::data::CLASS_OTHER},
+                        ParsedEdge{id_from: 5003, id_to: 5000, length: 10000.0, constraints: ::data::FLAG_CAR, speed: 13.89, tmc_id: Vec::new(), name: ::strings::NONE, road_ref: ::strings::NONE, class: ::data::CLASS_OTHER},
+                        ParsedEdge{id_from: 5003, id_to: 5004, length: 100000.0, constraints: ::data::FLAG_CAR, speed: 13.89, tmc_id: Vec::new(), name: ::strings::NONE, road_ref: ::strings::NONE, class: ::data::CLASS_OTHER},
 ];
 
 
@@ -150,11 +275,11 @@ pub fn build_dummy_data() -> ::data::State {
 	nodes_map.insert(5003, ::data::Position { lat: 0.0, lon: 0.0 });
 	nodes_map.insert(5004, ::data::Position { lat: 0.0, lon: 0.0 });
 
-	let parse_result = ParseData { nodes: nodes_map, edges: edge_vec, filtered_ways: HashMap::new(), nodes_used: HashSet::new(), tmc_next: HashMap::new() };
+	let parse_result = ParseData { nodes: nodes_map, edges: edge_vec, ..ParseData::new() };
 
-	let routing_data = build_routing_data(parse_result);
-	let grid = build_grid(&routing_data);
-	::data::State { routing_data: routing_data, grid: grid }
+	let mut routing_data = build_routing_data(parse_result);
+	filter_components(&mut routing_data);
+	routing_data
 }
 
 
@@ -186,8 +311,20 @@ fn first_parse(filename: &OsString, parse_result: &mut ParseData) {
 						}
 					}
 
+					let name = parse_result.names.add_option(way.tags.get("name"));
+					let road_ref = parse_result.names.add_option(way.tags.get("ref"));
+					parse_result.way_names.insert(way.id, (name, road_ref));
+					parse_result.way_classes.insert(way.id, way.tags.get("highway").map(|highway| road_class(highway)).unwrap_or(::data::CLASS_OTHER));
+
 					parse_result.filtered_ways.insert(way.id, constraints);
 				}
+
+				if let Some((housenumber, street)) = parse_address(&way.tags, &mut parse_result.names) {
+					for node in &way.nodes {
+						parse_result.address_nodes_used.insert(*node);
+					}
+					parse_result.address_ways.push((way.nodes.clone(), housenumber, street));
+				}
 			}
 			_ => {}
 		}
@@ -201,13 +338,41 @@ fn second_parse(filename: &OsString, parse_result: &mut ParseData) {
 	for obj in pbf.iter() {
 		match obj {
 			OsmObj::Node(node) => {
+				let position = ::data::Position { lat: node.lat, lon: node.lon };
 				if parse_result.nodes_used.remove(&node.id) {
-					parse_result.nodes.insert(node.id, ::data::Position { lat: node.lat, lon: node.lon });
+					parse_result.nodes.insert(node.id, position);
+				}
+				if parse_result.address_nodes_used.remove(&node.id) {
+					parse_result.address_nodes.insert(node.id, position);
+				}
+				if let Some((housenumber, street)) = parse_address(&node.tags, &mut parse_result.names) {
+					parse_result.addresses.push(::data::Address { position: ::data::RoutingNode::from_position(&position), housenumber: housenumber, street: street });
 				}
 			}
 			_ => {}
 		}
 	}
+
+	// buildings are placed at the center of their nodes
+	for (nodes, housenumber, street) in mem::replace(&mut parse_result.address_ways, Vec::new()) {
+		let positions: Vec<&::data::Position> = nodes.iter().filter_map(|node| parse_result.address_nodes.get(node)).collect();
+		if positions.is_empty() {
+			continue;
+		}
+
+		let count = positions.len() as f64;
+		let center = ::data::Position { lat: positions.iter().map(|p| p.lat).sum::<f64>() / count, lon: positions.iter().map(|p| p.lon).sum::<f64>() / count };
+		parse_result.addresses.push(::data::Address { position: ::data::RoutingNode::from_position(&center), housenumber: housenumber, street: street });
+	}
+	parse_result.address_nodes.clear();
+}
+
+// string ids of the house number and the street (or place, for addresses without streets)
+fn parse_address(tags: &::osmpbfreader::Tags, names: &mut ::strings::StringTableBuilder) -> Option<(u32, u32)> {
+	let housenumber = tags.get("addr:housenumber")?;
+	let street = tags.get("addr:street").or(tags.get("addr:place"));
+
+	Some((names.add(housenumber), names.add_option(street)))
 }
 
 fn third_parse(filename: &OsString, parse_result: &mut ParseData) {
@@ -219,12 +384,14 @@ fn third_parse(filename: &OsString, parse_result: &mut ParseData) {
 			OsmObj::Way(way) => {
 				let one_way = check_oneway(&way);
 				if let Some(constraints) = parse_result.filtered_ways.remove(&way.id) {
+					let (name, road_ref) = parse_result.way_names.remove(&way.id).unwrap_or((::strings::NONE, ::strings::NONE));
+					let class = parse_result.way_classes.remove(&way.id).unwrap_or(::data::CLASS_OTHER);
 					for node_pair in way.nodes.windows(2) {
 						if let (Some(from), Some(to)) = (node_pair.first(), node_pair.last()) {
 							if let (Some(from_node), Some(to_node)) = (parse_result.nodes.get(from), parse_result.nodes.get(to)) {
 								let edge_length = from_node.distance(&to_node);
-								let edge = ParsedEdge { id_from: *from, id_to: *to, length: edge_length, constraints: constraints.access, speed: constraints.speed, tmc_id: constraints.tmc_id.clone() };
-								let edge_reverse = ParsedEdge { id_from: *to, id_to: *from, length: edge_length, constraints: constraints.access, speed: constraints.speed, tmc_id: constraints.tmc_id.clone() };
+								let edge = ParsedEdge { id_from: *from, id_to: *to, length: edge_length, constraints: constraints.access, speed: constraints.speed, tmc_id: constraints.tmc_id.clone(), name: name, road_ref: road_ref, class: class };
+								let edge_reverse = ParsedEdge { id_from: *to, id_to: *from, length: edge_length, constraints: constraints.access, speed: constraints.speed, tmc_id: constraints.tmc_id.clone(), name: name, road_ref: road_ref, class: class };
 
 								match one_way {
 									OneWay::NO => {
@@ -245,9 +412,11 @@ fn third_parse(filename: &OsString, parse_result: &mut ParseData) {
 }
 
 fn build_routing_data(mut parse_result: ParseData) -> ::data::RoutingData {
-	let mut routing_data = ::data::RoutingData { osm_nodes: HashMap::new(), internal_nodes: Vec::new(), internal_edges: Vec::new(), internal_offset: vec![usize::max_value(); parse_result.nodes.len()], tmc_mapping: HashMap::new(), tmc_next: HashMap::new() };
+	let mut routing_data = ::data::RoutingData { internal_nodes: ::flat::FlatVec::new(), node_positions: ::flat::FlatVec::new(), internal_edges: ::flat::FlatVec::new(), internal_offset: vec![u32::max_value(); parse_result.nodes.len()].into(), tmc_mapping: HashMap::new(), tmc_next: HashMap::new(), tmc_points: HashMap::new(), node_components: ::flat::FlatVec::new(), edge_names: ::flat::FlatVec::new(), edge_refs: ::flat::FlatVec::new(), edge_classes: ::flat::FlatVec::new() };
 
 	let mut temp_tmc_store = HashMap::new();
+	// osm_n_id -> n_id, only used during the build
+	let mut internal_ids = HashMap::new();
 
 
 	parse_result.edges.sort_by(|a, b| b.id_from.cmp(&a.id_from));
@@ -261,14 +430,15 @@ fn build_routing_data(mut parse_result: ParseData) -> ::data::RoutingData {
 
 	for (i, node) in routing_data.internal_nodes.iter().enumerate() {
 		if let Some(pos) = parse_result.nodes.remove(node) {
-			routing_data.osm_nodes.insert(node.clone(), ::data::OsmNode { position: pos, internal_id: i });
+			routing_data.node_positions.push(::data::RoutingNode::from_position(&pos));
+			internal_ids.insert(*node, i as u32);
 		}
 	}
 
 	for (i, node) in routing_data.internal_nodes.iter().enumerate() {
 		if let Some(edge) = parse_result.edges.last() {
 			if edge.id_from == *node {
-				routing_data.internal_offset[i] = routing_data.internal_edges.len();
+				routing_data.internal_offset[i] = routing_data.internal_edges.len() as u32;
 			}
 		}
 
@@ -281,10 +451,13 @@ fn build_routing_data(mut parse_result: ParseData) -> ::data::RoutingData {
 				break;
 			}
 			if let Some(edge) = parse_result.edges.pop() {
-				let internal_source = routing_data.osm_nodes.get(&edge.id_from).unwrap().internal_id;
-				let internal_target = routing_data.osm_nodes.get(&edge.id_to).unwrap().internal_id;
+				let internal_source = *internal_ids.get(&edge.id_from).unwrap();
+				let internal_target = *internal_ids.get(&edge.id_to).unwrap();
 
-				routing_data.internal_edges.push(::data::RoutingEdge { source: internal_source, target: internal_target, length: edge.length, constraints: edge.constraints, speed: edge.speed });
+				routing_data.internal_edges.push(::data::RoutingEdge { source: internal_source, target: internal_target, length: edge.length as f32, constraints: edge.constraints, speed: edge.speed as f32 });
+				routing_data.edge_names.push(edge.name);
+				routing_data.edge_refs.push(edge.road_ref);
+				routing_data.edge_classes.push(edge.class);
 				temp_tmc_store.insert(routing_data.internal_edges.len() - 1, edge.tmc_id);
 			} else {
 				break;
@@ -292,10 +465,10 @@ fn build_routing_data(mut parse_result: ParseData) -> ::data::RoutingData {
 		}
 	}
 
-	let mut current_offset = routing_data.internal_edges.len() - 1;
+	let mut current_offset = routing_data.internal_edges.len() as u32;
 
 	for offset in &mut routing_data.internal_offset.iter_mut().rev() {
-		if *offset == usize::max_value() {
+		if *offset == u32::max_value() {
 			*offset = current_offset;
 		} else {
 			current_offset = *offset;
@@ -317,58 +490,161 @@ fn build_routing_data(mut parse_result: ParseData) -> ::data::RoutingData {
 	return routing_data;
 }
 
-fn build_grid(routing_data: &::data::RoutingData) -> ::data::Grid {
-	let mut bbox = calculate_bounding_box(&routing_data);
+// marks the nodes of the largest strongly connected component per profile and removes the
+// profile from all edges outside of it, so neither snapping nor routing ends up on islands
+fn filter_components(routing_data: &mut ::data::RoutingData) {
+	routing_data.node_components = vec![0; routing_data.internal_nodes.len()].into();
 
-	let grid_padding = 0.001;
+	for profile in ::data::PROFILES.iter() {
+		let (component, component_cnt) = find_components(routing_data, *profile);
 
-	bbox.min_lat -= grid_padding;
-	bbox.min_lon -= grid_padding;
-	bbox.max_lat += grid_padding;
-	bbox.max_lon += grid_padding;
+		let mut component_size = vec![0usize; component_cnt];
+		for c in &component {
+			component_size[*c] += 1;
+		}
 
-	let bin_count = routing_data.osm_nodes.len() / 1024;
+		let largest = match component_size.iter().enumerate().max_by_key(|&(_, size)| *size) {
+			Some((c, size)) if *size > 1 => c,
+			_ => {
+				println!("C  | profile {}: no connected component found", profile);
+				continue;
+			},
+		};
+
+		for (node, c) in component.iter().enumerate() {
+			if *c == largest {
+				routing_data.node_components[node] |= *profile;
+			}
+		}
 
-	let cnt_lat = (bin_count / ((bbox.max_lat - bbox.min_lat) as usize)) / 2;
-	let cnt_lon = (bin_count / ((bbox.max_lon - bbox.min_lon) as usize)) / 2;
+		let mut removed = 0;
+		for edge in routing_data.internal_edges.iter_mut() {
+			if edge.constraints & *profile != 0 && (component[edge.source as usize] != largest || component[edge.target as usize] != largest) {
+				edge.constraints &= *profile ^ 0xff;
+				removed += 1;
+			}
+		}
 
-	let mut grid = ::data::Grid { bbox: bbox, bins: vec![::data::Bin{nodes: Vec::new()}; cnt_lat * cnt_lon], bin_count_lat: cnt_lat, bin_count_lon: cnt_lon };
+		println!("C  | profile {}: {} components, largest has {} nodes, removed {} edges", profile, component_cnt, component_size[largest], removed);
+	}
+}
 
-	for (id, node) in &routing_data.osm_nodes {
-		let (lat_bin, lon_bin) = grid.calc_bin_index(&node.position);
+// iterative tarjan, returns the component of every node and the number of components
+fn find_components(routing_data: &::data::RoutingData, profile: u8) -> (Vec<usize>, usize) {
+	let node_count = routing_data.internal_nodes.len();
+	let unvisited = usize::max_value();
 
-		let index = grid.calc_bin_position(lat_bin, lon_bin);
+	let mut index = vec![unvisited; node_count];
+	let mut lowlink = vec![0; node_count];
+	let mut on_stack = vec![false; node_count];
+	let mut component = vec![unvisited; node_count];
 
-		if let Some(bin) = grid.bins.get_mut(index) {
-			bin.nodes.push(*id);
-		} else {
-			println!("error inserting node, {} is out of bounds of bin array {}", index, bin_count);
+	let mut stack = Vec::new();
+	// (node, next edge to visit)
+	let mut call_stack: Vec<(usize, usize)> = Vec::new();
+
+	let mut next_index = 0;
+	let mut component_cnt = 0;
+
+	for root in 0..node_count {
+		if index[root] != unvisited {
+			continue;
+		}
+
+		index[root] = next_index;
+		lowlink[root] = next_index;
+		next_index += 1;
+		stack.push(root);
+		on_stack[root] = true;
+		call_stack.push((root, routing_data.edge_range(root).0));
+
+		while let Some(&(node, edge_id)) = call_stack.last() {
+			let (_, end) = routing_data.edge_range(node);
+
+			if edge_id < end {
+				if let Some(top) = call_stack.last_mut() {
+					top.1 += 1;
+				}
+
+				let ref edge = routing_data.internal_edges[edge_id];
+				if edge.constraints & profile == 0 {
+					continue;
+				}
+
+				let target = edge.target as usize;
+
+				if index[target] == unvisited {
+					index[target] = next_index;
+					lowlink[target] = next_index;
+					next_index += 1;
+					stack.push(target);
+					on_stack[target] = true;
+					call_stack.push((target, routing_data.edge_range(target).0));
+				} else if on_stack[target] {
+					lowlink[node] = cmp::min(lowlink[node], index[target]);
+				}
+			} else {
+				call_stack.pop();
+
+				if let Some(&(parent, _)) = call_stack.last() {
+					lowlink[parent] = cmp::min(lowlink[parent], lowlink[node]);
+				}
+
+				if lowlink[node] == index[node] {
+					while let Some(member) = stack.pop() {
+						on_stack[member] = false;
+						component[member] = component_cnt;
+						if member == node {
+							break;
+						}
+					}
+					component_cnt += 1;
+				}
+			}
 		}
 	}
 
-	grid
+	(component, component_cnt)
 }
 
-fn calculate_bounding_box(routing_data: &::data::RoutingData) -> ::data::BoundingBox {
-	let mut bbox = ::data::BoundingBox { max_lat: f64::NEG_INFINITY, max_lon: f64::NEG_INFINITY, min_lat: f64::INFINITY, min_lon: f64::INFINITY };
+// indexes all edges, the profile is checked when querying
+fn build_edge_index(routing_data: &::data::RoutingData) -> ::rtree::RTree {
+	let rects: Vec<::rtree::Rect> = routing_data.internal_edges.iter()
+		.map(|edge| ::rtree::Rect::from_nodes(&routing_data.node_positions[edge.source as usize], &routing_data.node_positions[edge.target as usize]))
+		.collect();
 
-	for (_, node) in &routing_data.osm_nodes {
-		if node.position.lat > bbox.max_lat {
-			bbox.max_lat = node.position.lat;
-		}
-		if node.position.lon > bbox.max_lon {
-			bbox.max_lon = node.position.lon;
-		}
-		if node.position.lat < bbox.min_lat {
-			bbox.min_lat = node.position.lat;
-		}
-		if node.position.lon < bbox.min_lon {
-			bbox.min_lon = node.position.lon;
+	::rtree::RTree::build(&rects)
+}
+
+fn build_address_index(addresses: &[::data::Address]) -> ::rtree::RTree {
+	let rects: Vec<::rtree::Rect> = addresses.iter().map(|address| ::rtree::Rect::from_nodes(&address.position, &address.position)).collect();
+
+	::rtree::RTree::build(&rects)
+}
+
+// stable (fnv-1a) hash of the way filter lists, stored in the state header to detect outdated states
+pub fn profile_hash() -> u64 {
+	let defaults = init_filter_lists();
+
+	let mut highways: Vec<_> = defaults.lookup.keys().collect();
+	highways.sort();
+
+	let mut hash: u64 = 0xcbf29ce484222325;
+	let mut feed = |bytes: &[u8]| {
+		for byte in bytes {
+			hash ^= *byte as u64;
+			hash = hash.wrapping_mul(0x100000001b3);
 		}
+	};
+
+	for highway in highways {
+		let constraints = &defaults.lookup[highway];
+		feed(highway.as_bytes());
+		feed(format!("{}|{}", constraints.speed, constraints.access).as_bytes());
 	}
-	bbox
-}
 
+	hash
+}
 
 fn init_filter_lists() -> WayDefaults {
 	let mut defaults = WayDefaults { lookup: HashMap::new() };
@@ -404,6 +680,17 @@ fn init_filter_lists() -> WayDefaults {
 	return defaults;
 }
 
+fn road_class(highway: &str) -> u8 {
+	match highway.trim_end_matches("_link") {
+		"motorway" => ::data::CLASS_MOTORWAY,
+		"trunk" => ::data::CLASS_TRUNK,
+		"primary" => ::data::CLASS_PRIMARY,
+		"secondary" => ::data::CLASS_SECONDARY,
+		"tertiary" => ::data::CLASS_TERTIARY,
+		_ => ::data::CLASS_OTHER,
+	}
+}
+
 fn filter_way(way: &::osmpbfreader::Way, defaults: &WayDefaults) -> Option<WayConstraints> {
 	if let Some(value) = way.tags.get("highway") {
 		if let Some(default_constraints) = defaults.lookup.get(&value.as_str()) {
@@ -428,19 +715,34 @@ fn filter_way(way: &::osmpbfreader::Way, defaults: &WayDefaults) -> Option<WayCo
 
 fn handle_tmc(way: &::osmpbfreader::Way) -> Option<HashSet<TMCInfo>> {
 	if let Some(value) = way.tags.get("tmc").or(way.tags.get("TMC")) {
-		return Some(parse_tmc_info(value));
+		let way_table = match (way.tags.get("tmc:cid").map(|cid| cid.parse::<u32>()), way.tags.get("tmc:tabcd").map(|tabcd| tabcd.parse::<u32>())) {
+			(Some(Ok(cid)), Some(Ok(tabcd))) => Some(::data::TMCTable { cid: cid, tabcd: tabcd }),
+			_ => None,
+		};
+		return Some(parse_tmc_info(value, way_table));
 	} else {
 		return None;
 	}
 }
 
-fn parse_tmc_info(value: &String) -> HashSet<TMCInfo> {
+// entries may be prefixed by an iso country code (DE:12345+12346), the tmc:cid and tmc:tabcd tags of the way take precedence
+fn parse_tmc_info(value: &String, way_table: Option<::data::TMCTable>) -> HashSet<TMCInfo> {
 	//println!("parsing >>{}<<", value);
 
 	let mut result = HashSet::new();
 
 	for tag in value.split(";") {
-		let stripped = tag.replace("DE:", "");
+		let (table, stripped) = match tag.find(':') {
+			Some(index) => match way_table.or(::data::TMCTable::from_iso(&tag[..index])) {
+				Some(table) => (table, &tag[index + 1..]),
+				None => {
+					println!{"warn unknown TMC country in tag: {}", value}
+					continue;
+				}
+			},
+			None => (way_table.unwrap_or(::data::DEFAULT_TMC_TABLE), tag),
+		};
+		let location = |id: u32| ::data::TMCLocation::new(table, id);
 
 		if stripped.contains('+') {
 			let numbers: Vec<&str> = stripped.split('+').collect();
@@ -448,7 +750,7 @@ fn parse_tmc_info(value: &String) -> HashSet<TMCInfo> {
 			let id_to = numbers[1].parse::<u32>();
 
 			if let (Ok(id_from_parsed), Ok(id_to_parsed)) = (id_from, id_to) {
-				result.insert(TMCInfo { id: id_from_parsed, next: Some(TMCNext { direction: true, next: id_to_parsed }) });
+				result.insert(TMCInfo { id: location(id_from_parsed), next: Some(TMCNext { direction: true, next: location(id_to_parsed) }) });
 			} else {
 				println!{"warn failed to parse TMC tag: {}", value}
 			}
@@ -458,7 +760,7 @@ fn parse_tmc_info(value: &String) -> HashSet<TMCInfo> {
 			let id_to = numbers[1].parse::<u32>();
 
 			if let (Ok(id_from_parsed), Ok(id_to_parsed)) = (id_from, id_to) {
-				result.insert(TMCInfo { id: id_from_parsed, next: Some(TMCNext { direction: false, next: id_to_parsed }) });
+				result.insert(TMCInfo { id: location(id_from_parsed), next: Some(TMCNext { direction: false, next: location(id_to_parsed) }) });
 			} else {
 				println!{"warn failed to parse TMC tag: {}", value}
 			}
@@ -468,16 +770,15 @@ fn parse_tmc_info(value: &String) -> HashSet<TMCInfo> {
 			let id_to = numbers[1].parse::<u32>();
 
 			if let (Ok(id_from_parsed), Ok(id_to_parsed)) = (id_from, id_to) {
-				result.insert(TMCInfo { id: id_from_parsed, next: Some(TMCNext { direction: true, next: id_to_parsed }) });
-				result.insert(TMCInfo { id: id_to_parsed, next: Some(TMCNext { direction: false, next: id_from_parsed }) });
+				result.insert(TMCInfo { id: location(id_from_parsed), next: Some(TMCNext { direction: true, next: location(id_to_parsed) }) });
+				result.insert(TMCInfo { id: location(id_to_parsed), next: Some(TMCNext { direction: false, next: location(id_from_parsed) }) });
 			} else {
 				println!{"warn failed to parse TMC tag: {}", value}
 			}
 		} else {
 			let id_from = stripped.parse::<u32>();
 			if let Ok(id_from_parsed) = id_from {
-				result.insert(TMCInfo { id: id_from_parsed, next: None });
-				result.insert(TMCInfo { id: id_from_parsed, next: None });
+				result.insert(TMCInfo { id: location(id_from_parsed), next: None });
 			} else {
 				println!{"warn failed to parse TMC tag: {}", value}
 			}
@@ -0,0 +1,338 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use tmc_source::TMCSource;
+
+// group type code of block B (4 bit type and version, A = 0)
+const GROUP_3A: u16 = 0b00110;
+const GROUP_8A: u16 = 0b10000;
+// application ids of ALERT-C in type 3A groups
+const TMC_AIDS: [u16; 2] = [0xcd46, 0xcd47];
+
+// [label] -> size of the optional content value in bits
+const LABEL_BITS: [usize; 16] = [3, 3, 5, 5, 5, 8, 8, 8, 8, 11, 16, 16, 16, 16, 0, 0];
+// [label] -> key of the value in GM lines
+const LABEL_KEYS: [&'static str; 16] = ["dur", "ctl", "len", "spd", "q5", "q8", "sup", "start", "stop", "evt2", "divr", "dest", "prec", "xlink", "sep", "res"];
+// free format bits per subsequent group
+const FREE_FORMAT_BITS: usize = 28;
+
+// the tmc relevant content of a single rds group
+#[derive(Debug, Clone, PartialEq)]
+pub enum Group {
+	Single { event: u16, location: u16, extent: u8, direction: bool, diversion: bool, duration: u8 },
+	// first group of a multi-group message, ci (continuity index) links the subsequent groups
+	First { ci: u8, event: u16, location: u16, extent: u8, direction: bool },
+	// second marks the group following the first one (unknown in rdsquery lines), gsi counts down
+	// the remaining groups, f1 and f2 are 12 and 16 bits of optional content
+	Subsequent { ci: u8, second: Option<bool>, gsi: u8, f1: u16, f2: u16 },
+	// type 3A variant 0, country is the first digit of the pi code
+	SystemInfo { country: u8, ltn: u8, afi: bool, mode: bool },
+	// type 3A variant 1
+	ServiceInfo { country: u8, sid: u8, gap: u8 },
+}
+
+impl Group {
+	// the same line format rdsquery prints, plus SI lines for system information
+	pub fn to_line(&self) -> String {
+		match *self {
+			Group::Single { event, location, extent, direction, diversion, duration } =>
+				format!("S evt={} loc={} ext={} dur={} dir={} div={}", event, location, extent, duration, direction as u8, diversion as u8),
+			Group::First { ci, event, location, extent, direction } =>
+				format!("GF evt={} loc={} ext={} CI={} dir={}", event, location, extent, ci, direction as u8),
+			Group::Subsequent { ci, second, gsi, f1, f2 } => match second {
+				Some(second) => format!("GS CI={} GSI={} F1={} F2={} SG={}", ci, gsi, f1, f2, second as u8),
+				None => format!("GS CI={} GSI={} F1={} F2={}", ci, gsi, f1, f2),
+			},
+			Group::SystemInfo { country, ltn, afi, mode } =>
+				format!("SI CC={:X} LTN={} AFI={} M={}", country, ltn, afi as u8, mode as u8),
+			Group::ServiceInfo { country, sid, gap } =>
+				format!("SI CC={:X} SID={} G={}", country, sid, gap),
+		}
+	}
+
+	// the reverse of to_line, the country code of SI lines is hexadecimal
+	pub fn parse_line(line: &str) -> Option<Group> {
+		let mut vars = line.split_whitespace();
+		let kind = vars.next()?;
+
+		let mut values = HashMap::new();
+		for pair in vars {
+			let mut split_pair = pair.splitn(2, '=');
+			if let (Some(key), Some(Ok(value))) = (split_pair.next(), split_pair.next().map(|value| value.parse::<u32>())) {
+				values.insert(key, value);
+			}
+		}
+		let value = |key: &str| values.get(key).cloned();
+		let country = || line.split_whitespace().filter_map(|pair| if pair.starts_with("CC=") { u8::from_str_radix(&pair[3..], 16).ok() } else { None }).next();
+
+		match kind {
+			"S" => Some(Group::Single { event: value("evt")? as u16, location: value("loc")? as u16, extent: value("ext").unwrap_or(0) as u8, direction: value("dir") == Some(1), diversion: value("div") == Some(1), duration: value("dur").unwrap_or(0) as u8 }),
+			"GF" => Some(Group::First { ci: value("CI")? as u8, event: value("evt")? as u16, location: value("loc")? as u16, extent: value("ext").unwrap_or(0) as u8, direction: value("dir") == Some(1) }),
+			"GS" => Some(Group::Subsequent { ci: value("CI")? as u8, second: value("SG").map(|second| second == 1), gsi: value("GSI")? as u8, f1: value("F1")? as u16, f2: value("F2")? as u16 }),
+			"SI" if values.contains_key("LTN") => Some(Group::SystemInfo { country: country()?, ltn: value("LTN")? as u8, afi: value("AFI") == Some(1), mode: value("M") == Some(1) }),
+			"SI" => Some(Group::ServiceInfo { country: country()?, sid: value("SID")? as u8, gap: value("G").unwrap_or(0) as u8 }),
+			_ => None,
+		}
+	}
+}
+
+// four blocks as printed by most rds tools ("D22A 8406 4D2E 1234"), groups with missing blocks are skipped
+pub fn parse_hex_group(line: &str) -> Option<[u16; 4]> {
+	let mut blocks = [0u16; 4];
+	let mut tokens = line.split_whitespace();
+
+	for block in blocks.iter_mut() {
+		let token = tokens.next()?;
+		if token.len() != 4 {
+			return None;
+		}
+		*block = u16::from_str_radix(token, 16).ok()?;
+	}
+	Some(blocks)
+}
+
+// None for all groups without tmc content and for 8A tuning information
+pub fn decode_group(blocks: &[u16; 4]) -> Option<Group> {
+	let (a, b, c, d) = (blocks[0], blocks[1], blocks[2], blocks[3]);
+	let bit = |value: u16, index: u16| (value >> index) & 1 == 1;
+
+	match b >> 11 {
+		GROUP_8A => {
+			if bit(b, 4) {
+				return None;
+			}
+
+			let event = c & 0x7ff;
+			let extent = ((c >> 11) & 0x7) as u8;
+
+			if bit(b, 3) {
+				Some(Group::Single { event: event, location: d, extent: extent, direction: bit(c, 14), diversion: bit(c, 15), duration: (b & 0x7) as u8 })
+			} else if bit(c, 15) {
+				Some(Group::First { ci: (b & 0x7) as u8, event: event, location: d, extent: extent, direction: bit(c, 14) })
+			} else {
+				Some(Group::Subsequent { ci: (b & 0x7) as u8, second: Some(bit(c, 14)), gsi: ((c >> 12) & 0x3) as u8, f1: c & 0xfff, f2: d })
+			}
+		},
+		GROUP_3A if TMC_AIDS.contains(&d) && b & 0x1f == GROUP_8A => {
+			let country = (a >> 12) as u8;
+			match c >> 14 {
+				0 => Some(Group::SystemInfo { country: country, ltn: ((c >> 6) & 0x3f) as u8, afi: bit(c, 5), mode: bit(c, 4) }),
+				1 => Some(Group::ServiceInfo { country: country, sid: ((c >> 6) & 0x3f) as u8, gap: ((c >> 12) & 0x3) as u8 }),
+				_ => None,
+			}
+		},
+		_ => None,
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OptionalContent {
+	pub label: u8,
+	pub value: u32,
+}
+
+// label/value pairs of the concatenated free format bits, trailing zero bits are padding
+pub fn decode_optional_content(bits: &[bool]) -> Vec<OptionalContent> {
+	let mut result = Vec::new();
+	let mut position = 0;
+
+	let read = |position: &mut usize, count: usize| {
+		let value = bits[*position..*position + count].iter().fold(0u32, |value, bit| (value << 1) | *bit as u32);
+		*position += count;
+		value
+	};
+
+	while bits.len() - position >= 4 && bits[position..].iter().any(|bit| *bit) {
+		let label = read(&mut position, 4) as u8;
+		let size = LABEL_BITS[label as usize];
+		if label == 15 || bits.len() - position < size {
+			break;
+		}
+		result.push(OptionalContent { label: label, value: read(&mut position, size) });
+	}
+
+	result
+}
+
+// a complete multi-group message
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+	pub ci: u8,
+	pub event: u16,
+	pub location: u16,
+	pub extent: u8,
+	pub direction: bool,
+	pub optional: Vec<OptionalContent>,
+}
+
+impl Message {
+	// GM lines look like GF lines with the optional content appended, labels may repeat
+	pub fn to_line(&self) -> String {
+		let mut line = format!("GM evt={} loc={} ext={} CI={} dir={}", self.event, self.location, self.extent, self.ci, self.direction as u8);
+		for content in &self.optional {
+			line.push_str(&format!(" {}={}", LABEL_KEYS[content.label as usize], content.value));
+		}
+		line
+	}
+}
+
+struct PendingMessage {
+	first: Group,
+	bits: Vec<bool>,
+	// gsi of the next group, None before the second group and after the last one
+	next_gsi: Option<u8>,
+}
+
+// collects the groups of multi-group messages per continuity index
+pub struct Assembler {
+	pending: HashMap<u8, PendingMessage>,
+}
+
+impl Assembler {
+	pub fn new() -> Assembler {
+		Assembler { pending: HashMap::new() }
+	}
+
+	// the complete message once its last group arrived, messages with missing groups are dropped
+	pub fn push(&mut self, group: &Group) -> Option<Message> {
+		match *group {
+			Group::First { ci, .. } => {
+				self.pending.insert(ci, PendingMessage { first: group.clone(), bits: Vec::new(), next_gsi: None });
+				None
+			},
+			Group::Subsequent { ci, second, gsi, f1, f2 } => {
+				let complete = {
+					let pending = self.pending.get_mut(&ci)?;
+					if second.unwrap_or(pending.next_gsi.is_none()) {
+						pending.bits.clear();
+					} else if pending.next_gsi != Some(gsi) {
+						self.pending.remove(&ci);
+						return None;
+					}
+
+					let free_format = ((f1 as u32 & 0xfff) << 16) | f2 as u32;
+					pending.bits.extend((0..FREE_FORMAT_BITS).rev().map(|index| (free_format >> index) & 1 == 1));
+					pending.next_gsi = gsi.checked_sub(1);
+					gsi == 0
+				};
+
+				if !complete {
+					return None;
+				}
+
+				match self.pending.remove(&ci) {
+					Some(PendingMessage { first: Group::First { ci, event, location, extent, direction }, bits, .. }) =>
+						Some(Message { ci: ci, event: event, location: location, extent: extent, direction: direction, optional: decode_optional_content(&bits) }),
+					_ => None,
+				}
+			},
+			_ => None,
+		}
+	}
+}
+
+// turns raw groups into receiver lines, complete multi-group messages additionally produce a GM line
+pub struct Decoder {
+	assembler: Assembler,
+	// groups are usually transmitted twice in a row
+	last: Option<[u16; 4]>,
+}
+
+impl Decoder {
+	pub fn new() -> Decoder {
+		Decoder { assembler: Assembler::new(), last: None }
+	}
+
+	pub fn push(&mut self, blocks: &[u16; 4]) -> Vec<String> {
+		let mut lines = Vec::new();
+		if self.last == Some(*blocks) {
+			return lines;
+		}
+		self.last = Some(*blocks);
+
+		if let Some(group) = decode_group(blocks) {
+			lines.push(group.to_line());
+			if let Some(message) = self.assembler.push(&group) {
+				lines.push(message.to_line());
+			}
+		}
+		lines
+	}
+}
+
+// reads hex groups from another source
+pub struct RdsSource {
+	inner: Box<dyn TMCSource>,
+	decoder: Decoder,
+	pending: VecDeque<String>,
+}
+
+impl RdsSource {
+	pub fn new(inner: Box<dyn TMCSource>) -> RdsSource {
+		RdsSource { inner: inner, decoder: Decoder::new(), pending: VecDeque::new() }
+	}
+}
+
+impl TMCSource for RdsSource {
+	fn name(&self) -> String {
+		format!("rds groups from {}", self.inner.name())
+	}
+
+	fn next_line(&mut self) -> Option<String> {
+		while self.pending.is_empty() {
+			let line = self.inner.next_line()?;
+			if let Some(blocks) = parse_hex_group(&line) {
+				self.pending.extend(self.decoder.push(&blocks));
+			}
+		}
+		self.pending.pop_front()
+	}
+
+	// decoded lines are returned once their last group arrived
+	fn received(&self) -> i64 {
+		self.inner.received()
+	}
+}
+
+#[test]
+fn test_decode_groups() {
+	assert_eq!(parse_hex_group("D22A 8406 4D2E 1234"), Some([0xd22a, 0x8406, 0x4d2e, 0x1234]));
+	assert_eq!(parse_hex_group("D22A 8406 ---- 1234"), None);
+
+	// single group: duration 2, direction 1, extent 2, event 101 at location 12345
+	let single = [0xd22a, (GROUP_8A << 11) | 0b01010, (1 << 14) | (2 << 11) | 101, 12345];
+	assert_eq!(decode_group(&single).unwrap().to_line(), "S evt=101 loc=12345 ext=2 dur=2 dir=1 div=0");
+
+	// system information of location table 1 in germany
+	let system = [0xd22a, (GROUP_3A << 11) | GROUP_8A, 1 << 6, 0xcd46];
+	assert_eq!(decode_group(&system).unwrap().to_line(), "SI CC=D LTN=1 AFI=0 M=0");
+
+	// multi-group message with ci 5: quantifier 3 and additional event 1000 in two subsequent groups
+	let first = [0xd22a, (GROUP_8A << 11) | 5, (1 << 15) | (1 << 11) | 803, 45112];
+	let optional: u64 = (4 << 52) | (3 << 47) | (9 << 43) | (1000 << 32);
+	let second = [0xd22a, (GROUP_8A << 11) | 5, (1 << 14) | (1 << 12) | ((optional >> 44) & 0xfff) as u16, ((optional >> 28) & 0xffff) as u16];
+	let third = [0xd22a, (GROUP_8A << 11) | 5, ((optional >> 16) & 0xfff) as u16, (optional & 0xffff) as u16];
+
+	let mut decoder = Decoder::new();
+	assert_eq!(decoder.push(&first), vec!["GF evt=803 loc=45112 ext=1 CI=5 dir=0".to_string()]);
+	assert_eq!(decoder.push(&first).len(), 0);
+	assert_eq!(decoder.push(&second).len(), 1);
+	assert_eq!(decoder.push(&third), vec![
+		format!("GS CI=5 GSI=0 F1={} F2={} SG=0", (optional >> 16) & 0xfff, optional & 0xffff),
+		"GM evt=803 loc=45112 ext=1 CI=5 dir=0 q5=3 evt2=1000".to_string(),
+	]);
+
+	// a missing group drops the message
+	decoder.push(&first);
+	assert_eq!(decoder.push(&third).len(), 1);
+	assert_eq!(decoder.push(&second).len(), 1);
+	assert_eq!(decoder.push(&first).len(), 1);
+
+	// rdsquery lines don't mark the second group
+	let mut assembler = Assembler::new();
+	let lines = ["GF evt=803 loc=45112 ext=1 CI=5 dir=0".to_string(), decode_group(&second).unwrap().to_line().replace(" SG=1", ""), decode_group(&third).unwrap().to_line().replace(" SG=0", "")];
+	let messages: Vec<Message> = lines.iter().filter_map(|line| assembler.push(&Group::parse_line(line).unwrap())).collect();
+	assert_eq!(messages.len(), 1);
+	assert_eq!(messages[0].optional, vec![OptionalContent { label: 4, value: 3 }, OptionalContent { label: 9, value: 1000 }]);
+	assert_eq!(Group::parse_line("S evt=736 loc=36918 ext=2 dur=0 dir=0 div=0 "), Some(Group::Single { event: 736, location: 36918, extent: 2, direction: false, diversion: false, duration: 0 }));
+}
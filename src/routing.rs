@@ -0,0 +1,581 @@
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::f64;
+use std::sync::RwLock;
+use ordered_float::OrderedFloat;
+
+#[derive(Debug, Clone)]
+struct HeapEntry {
+	node: usize,
+	cost: f64,
+}
+
+#[derive(Debug, Clone, RustcEncodable, RustcDecodable)]
+pub struct Route {
+	pub distance: f64,
+	pub time: f64,
+	pub path: Vec<[f64; 2]>,
+	// [path index] -> seconds since the start, derived from the edge speeds
+	pub times: Vec<f64>,
+	// traversed edge ids, the first and the last one may be used partially
+	pub edges: Vec<usize>
+}
+
+impl Route {
+	// douglas-peucker, removes points closer than tolerance meters to the simplified line, times are kept for the remaining points
+	pub fn simplify(&mut self, tolerance: f64) {
+		if self.path.len() < 3 {
+			return;
+		}
+
+		let position = |point: &[f64; 2]| ::data::Position { lat: point[0], lon: point[1] };
+		let mut keep = vec![false; self.path.len()];
+		keep[0] = true;
+		keep[self.path.len() - 1] = true;
+
+		let mut stack = vec![(0, self.path.len() - 1)];
+		while let Some((first, last)) = stack.pop() {
+			let (a, b) = (position(&self.path[first]), position(&self.path[last]));
+
+			let mut max_distance = 0.0;
+			let mut max_index = first;
+			for i in first + 1..last {
+				let point = position(&self.path[i]);
+				let distance = point.distance(&point.project_onto(&a, &b).1);
+				if distance > max_distance {
+					max_distance = distance;
+					max_index = i;
+				}
+			}
+
+			if max_distance > tolerance {
+				keep[max_index] = true;
+				stack.push((first, max_index));
+				stack.push((max_index, last));
+			}
+		}
+
+		let mut index = 0;
+		self.path.retain(|_| { index += 1; keep[index - 1] });
+		let mut index = 0;
+		self.times.retain(|_| { index += 1; keep[index - 1] });
+	}
+}
+
+#[derive(Debug, Clone)]
+struct PredecessorInfo {
+	node: usize,
+	edge: usize
+}
+
+impl Ord for HeapEntry {
+	fn cmp(&self, other: &HeapEntry) -> Ordering {
+		OrderedFloat(other.cost).cmp(&OrderedFloat(self.cost))
+	}
+}
+
+impl PartialOrd for HeapEntry {
+	fn partial_cmp(&self, other: &HeapEntry) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Eq for HeapEntry {}
+
+impl PartialEq for HeapEntry {
+	fn eq(&self, other: &HeapEntry) -> bool {
+		return (self.node == other.node) & &(OrderedFloat(other.cost).eq(&OrderedFloat(self.cost)))
+	}
+}
+
+
+pub fn parse_position(raw: &str) -> Option<::data::Position> {
+	let mut split = raw.split(",");
+
+	if let (Some(lat_str), Some(lon_str)) = (split.next(), split.next()) {
+		if let (Ok(lat), Ok(lon)) = (lat_str.parse::<f64>(), lon_str.parse::<f64>()) {
+			return Some(::data::Position { lat: lat, lon: lon });
+		}
+	}
+
+	None
+}
+
+// "degrees" or "degrees,range", the range defaults to 45 degrees
+pub fn parse_bearing(raw: &str) -> Option<(f64, f64)> {
+	let mut split = raw.split(",");
+
+	let bearing = split.next().and_then(|bearing| bearing.parse::<f64>().ok())?;
+	let range = match split.next() {
+		Some(range) => range.parse::<f64>().ok()?,
+		None => 45.0,
+	};
+
+	Some((bearing, range))
+}
+
+// the second argument is the profile
+pub type CostFunction = fn(&::data::RoutingEdge, &u8, &usize, &::data::TMCState) -> f64;
+
+pub fn vehicle_constraints(vehicle: &str) -> u8 {
+	match vehicle {
+		"car" => ::data::FLAG_CAR,
+		"bike" => ::data::FLAG_BIKE,
+		"walk" => ::data::FLAG_WALK,
+		_ => ::data::FLAG_CAR
+	}
+}
+
+pub fn cost_function(metric: &str, use_tmc: bool) -> CostFunction {
+	match (metric, use_tmc) {
+		("time", true) => edge_cost_tmc,
+		("time", false) => edge_cost_time,
+		("distance", _) => edge_cost_distance,
+		_ => edge_cost_distance
+	}
+}
+
+// where a route starts or ends, somewhere along an edge
+#[derive(Debug, Clone, Copy)]
+pub enum Endpoint {
+	Edge(::data::Snap),
+}
+
+impl Endpoint {
+	fn snap(&self) -> &::data::Snap {
+		match *self {
+			Endpoint::Edge(ref snap) => snap,
+		}
+	}
+
+	fn position(&self) -> ::data::Position {
+		self.snap().position
+	}
+}
+
+#[derive(Debug, Clone, RustcEncodable, RustcDecodable)]
+pub struct SnapInfo {
+	pub position: [f64; 2],
+	pub distance: f64,
+}
+
+impl SnapInfo {
+	pub fn from_snap(snap: &::data::Snap) -> SnapInfo {
+		SnapInfo { position: [snap.position.lat, snap.position.lon], distance: snap.distance }
+	}
+}
+
+// the part of an edge between an endpoint and a graph node
+#[derive(Debug, Clone, Copy)]
+struct PartialEdge {
+	node: usize,
+	edge: usize,
+	fraction: f64,
+}
+
+// predecessor of nodes reached directly from the source endpoint
+const VIRTUAL_SOURCE: usize = ::std::usize::MAX;
+
+fn vehicle_speed(constraints: u8) -> f64 {
+	match constraints {
+		::data::FLAG_CAR => 130.0 / 3.6,
+		::data::FLAG_BIKE => 15.0 / 3.6,
+		::data::FLAG_WALK => 5.0 / 3.6,
+		_ => 130.0 / 3.6
+	}
+}
+
+// snaps with a requested bearing must not be used against it
+fn reverse_edge(data: &::data::RoutingData, snap: &::data::Snap) -> Option<usize> {
+	if snap.directed { None } else { data.reverse_edge(snap.edge) }
+}
+
+// the nodes reachable from the source endpoint, the snapped edge may be left in both directions
+fn source_partials(data: &::data::RoutingData, source: &Endpoint) -> Vec<PartialEdge> {
+	let snap = source.snap();
+	let edge = &data.internal_edges[snap.edge];
+	let mut partials = vec![PartialEdge { node: edge.target as usize, edge: snap.edge, fraction: 1.0 - snap.ratio }];
+	if let Some(reverse) = reverse_edge(data, snap) {
+		partials.push(PartialEdge { node: edge.source as usize, edge: reverse, fraction: snap.ratio });
+	}
+	partials
+}
+
+// the nodes from which the target endpoint can be reached
+fn target_partials(data: &::data::RoutingData, target: &Endpoint) -> Vec<PartialEdge> {
+	let snap = target.snap();
+	let edge = &data.internal_edges[snap.edge];
+	let mut partials = vec![PartialEdge { node: edge.source as usize, edge: snap.edge, fraction: snap.ratio }];
+	if let Some(reverse) = reverse_edge(data, snap) {
+		partials.push(PartialEdge { node: edge.target as usize, edge: reverse, fraction: 1.0 - snap.ratio });
+	}
+	partials
+}
+
+// source and target on the same edge (in either direction) can be connected without visiting a node
+fn direct_partials(data: &::data::RoutingData, source: &Endpoint, target: &Endpoint, virtual_target: usize) -> Vec<PartialEdge> {
+	let (source_snap, target_snap) = (source.snap(), target.snap());
+
+	let target_reverse = reverse_edge(data, target_snap);
+	let mut candidates = vec![(source_snap.edge, source_snap.ratio)];
+	if let Some(reverse) = reverse_edge(data, source_snap) {
+		candidates.push((reverse, 1.0 - source_snap.ratio));
+	}
+
+	let mut partials = Vec::new();
+	for (edge, source_ratio) in candidates {
+		let target_ratio = if edge == target_snap.edge {
+			target_snap.ratio
+		} else if target_reverse == Some(edge) {
+			1.0 - target_snap.ratio
+		} else {
+			continue;
+		};
+
+		if target_ratio >= source_ratio {
+			partials.push(PartialEdge { node: virtual_target, edge: edge, fraction: target_ratio - source_ratio });
+		}
+	}
+	partials
+}
+
+// the target endpoint is represented by a virtual node behind all graph nodes
+pub fn run_dijkstra_between<F>(data: &::data::RoutingData, source: &Endpoint, target: &Endpoint, constraints: u8, cost_func: F, tmc_state: &RwLock<::data::TMCState>) -> Option<Route>
+	where F: Fn(&::data::RoutingEdge, &u8, &usize, &::data::TMCState) -> f64 {
+	let vspeed = vehicle_speed(constraints);
+	let virtual_target = data.internal_nodes.len();
+
+	let mut distance = vec![f64::INFINITY; virtual_target + 1];
+	let mut predecessor = vec![VIRTUAL_SOURCE; virtual_target + 1];
+	let mut predecessor_edge: Vec<Option<usize>> = vec![None; virtual_target + 1];
+	// only the first and the last edge of a route can be partial
+	let mut predecessor_fraction = vec![1.0; virtual_target + 1];
+
+	let tmc = tmc_state.read().unwrap();
+
+	let usable = |partial: &PartialEdge| data.internal_edges[partial.edge].constraints & constraints != 0;
+	let partial_cost = |partial: &PartialEdge| partial.fraction * cost_func(&data.internal_edges[partial.edge], &constraints, &partial.edge, &tmc);
+
+	let mut heap = BinaryHeap::new();
+
+	let mut start_partials = source_partials(data, source);
+	start_partials.extend(direct_partials(data, source, target, virtual_target));
+
+	for partial in start_partials.iter().filter(|partial| usable(partial)) {
+		let cost = partial_cost(partial);
+
+		if cost < distance[partial.node] {
+			distance[partial.node] = cost;
+			predecessor_edge[partial.node] = Some(partial.edge);
+			predecessor_fraction[partial.node] = partial.fraction;
+			heap.push(HeapEntry { node: partial.node, cost: cost });
+		}
+	}
+
+	let end_partials: Vec<PartialEdge> = target_partials(data, target).into_iter().filter(|partial| usable(partial)).collect();
+
+	while let Some(HeapEntry { node, cost }) = heap.pop() {
+		if node == virtual_target {
+			return build_route(source, target, &predecessor, &predecessor_edge, &predecessor_fraction, &data, &vspeed);
+		}
+
+		if cost > distance[node] { continue; }
+
+		for partial in end_partials.iter().filter(|partial| partial.node == node) {
+			let target_cost = cost + partial_cost(partial);
+
+			if target_cost < distance[virtual_target] {
+				distance[virtual_target] = target_cost;
+				predecessor[virtual_target] = node;
+				predecessor_edge[virtual_target] = Some(partial.edge);
+				predecessor_fraction[virtual_target] = partial.fraction;
+				heap.push(HeapEntry { node: virtual_target, cost: target_cost });
+			}
+		}
+
+		let (start, end) = data.edge_range(node);
+		let edges = &data.internal_edges[start..end];
+
+		for (i, edge) in edges.iter().enumerate() {
+			if constraints & edge.constraints == 0 {
+				continue;
+			}
+
+			let neighbor = HeapEntry { node: edge.target as usize, cost: cost + cost_func(&edge, &constraints, &(i + start), &tmc) };
+
+			if neighbor.cost < distance[neighbor.node] {
+				distance[neighbor.node] = neighbor.cost;
+				predecessor[neighbor.node] = node;
+				predecessor_edge[neighbor.node] = Some(i + start);
+				predecessor_fraction[neighbor.node] = 1.0;
+				heap.push(neighbor);
+			}
+		}
+	}
+	return None;
+}
+
+
+// shortest paths by length from one source to several targets, None for targets further away than the limit
+pub struct BoundedSearch {
+	// target -> (length, node the target was reached from, last edge)
+	reached: Vec<Option<(f64, usize, usize)>>,
+	// node -> (previous node, edge), VIRTUAL_SOURCE for nodes reached from the source endpoint
+	predecessor: HashMap<usize, (usize, usize)>,
+}
+
+impl BoundedSearch {
+	pub fn distance(&self, target: usize) -> Option<f64> {
+		self.reached[target].map(|(length, _, _)| length)
+	}
+
+	// the edges from the source to the target, including the partial first and last edge
+	pub fn edges(&self, target: usize) -> Option<Vec<usize>> {
+		let (_, mut node, last) = self.reached[target]?;
+		let mut result = vec![last];
+
+		while node != VIRTUAL_SOURCE {
+			let (previous, edge) = self.predecessor[&node];
+			result.push(edge);
+			node = previous;
+		}
+		result.reverse();
+		Some(result)
+	}
+}
+
+// the search stops at max_distance and only keeps visited nodes, so it's cheap for nearby targets.
+// usable decides which edges may be used
+pub fn bounded_search<F>(data: &::data::RoutingData, source: &Endpoint, targets: &[Endpoint], usable: F, max_distance: f64) -> BoundedSearch
+	where F: Fn(usize) -> bool {
+	let mut result = BoundedSearch { reached: vec![None; targets.len()], predecessor: HashMap::new() };
+
+	let usable_partial = |partial: &PartialEdge| usable(partial.edge);
+	let partial_length = |partial: &PartialEdge| partial.fraction * data.internal_edges[partial.edge].length as f64;
+
+	// node -> (target index, remaining length, last edge)
+	let mut end_partials: HashMap<usize, Vec<(usize, f64, usize)>> = HashMap::new();
+	for (i, target) in targets.iter().enumerate() {
+		for partial in direct_partials(data, source, target, 0).iter().filter(|partial| usable_partial(partial)) {
+			let length = partial_length(partial);
+			if result.reached[i].map(|(known, _, _)| length < known).unwrap_or(true) {
+				result.reached[i] = Some((length, VIRTUAL_SOURCE, partial.edge));
+			}
+		}
+		for partial in target_partials(data, target).iter().filter(|partial| usable_partial(partial)) {
+			end_partials.entry(partial.node).or_insert(Vec::new()).push((i, partial_length(partial), partial.edge));
+		}
+	}
+
+	let mut distance: HashMap<usize, f64> = HashMap::new();
+	let mut heap = BinaryHeap::new();
+
+	for partial in source_partials(data, source).iter().filter(|partial| usable_partial(partial)) {
+		let length = partial_length(partial);
+		if distance.get(&partial.node).map(|known| length < *known).unwrap_or(true) {
+			distance.insert(partial.node, length);
+			result.predecessor.insert(partial.node, (VIRTUAL_SOURCE, partial.edge));
+			heap.push(HeapEntry { node: partial.node, cost: length });
+		}
+	}
+
+	while let Some(HeapEntry { node, cost }) = heap.pop() {
+		// all targets are settled once no shorter way to them can follow
+		if cost > max_distance || result.reached.iter().all(|reached| reached.map(|(length, _, _)| length <= cost).unwrap_or(false)) {
+			break;
+		}
+		if cost > distance[&node] { continue; }
+
+		if let Some(partials) = end_partials.get(&node) {
+			for &(i, length, edge) in partials {
+				if result.reached[i].map(|(known, _, _)| cost + length < known).unwrap_or(true) {
+					result.reached[i] = Some((cost + length, node, edge));
+				}
+			}
+		}
+
+		let (start, end) = data.edge_range(node);
+		for (i, edge) in data.internal_edges[start..end].iter().enumerate() {
+			if !usable(i + start) {
+				continue;
+			}
+
+			let neighbor = HeapEntry { node: edge.target as usize, cost: cost + edge.length as f64 };
+			if neighbor.cost <= max_distance && distance.get(&neighbor.node).map(|known| neighbor.cost < *known).unwrap_or(true) {
+				distance.insert(neighbor.node, neighbor.cost);
+				result.predecessor.insert(neighbor.node, (node, i + start));
+				heap.push(neighbor);
+			}
+		}
+	}
+
+	for reached in result.reached.iter_mut() {
+		if reached.map(|(length, _, _)| length > max_distance).unwrap_or(false) {
+			*reached = None;
+		}
+	}
+	result
+}
+
+fn build_route(source: &Endpoint, target: &Endpoint, predecessor: &Vec<usize>, predecessor_edge: &Vec<Option<usize>>, predecessor_fraction: &Vec<f64>, data: &::data::RoutingData, vspeed: &f64) -> Option<Route> {
+	let mut result = Route { distance: 0.0, time: 0.0, path: Vec::new(), times: Vec::new(), edges: Vec::new() };
+
+	let target_pos = target.position();
+	result.path.push([target_pos.lat, target_pos.lon]);
+	// seconds until the target while walking backwards, converted after the reverse
+	result.times.push(0.0);
+
+	let mut node = predecessor.len() - 1;
+
+	while node != VIRTUAL_SOURCE {
+		if let Some(edge) = predecessor_edge[node] {
+			result.edges.push(edge);
+			let ref edge = data.internal_edges[edge];
+			let length = edge.length as f64 * predecessor_fraction[node];
+
+			result.distance += length;
+			result.time += length / f64::min(*vspeed, edge.speed as f64);
+		}
+
+		node = predecessor[node];
+
+		let pos = if node == VIRTUAL_SOURCE { source.position() } else { data.position(node) };
+		// snaps onto an edge end would be added twice
+		if result.path.last() != Some(&[pos.lat, pos.lon]) {
+			result.path.push([pos.lat, pos.lon]);
+			result.times.push(result.time);
+		}
+	}
+
+	result.path.reverse();
+	result.times.reverse();
+	result.edges.reverse();
+	for time in result.times.iter_mut() {
+		*time = result.time - *time;
+	}
+
+	return Some(result);
+}
+
+pub fn edge_cost_distance(edge: &::data::RoutingEdge, _: &u8, _: &usize, _: &::data::TMCState) -> f64 {
+	return edge.length as f64;
+}
+
+// closed edges (slowdown 1.0) can't be passed, infinite costs are never relaxed
+pub fn edge_cost_tmc(edge: &::data::RoutingEdge, constraints: &u8, edge_id: &usize, state: &::data::TMCState) -> f64 {
+	let mut speed = edge.speed as f64;
+	let vspeed = vehicle_speed(*constraints);
+
+	let slowdown = state.slowdown(*edge_id, *constraints);
+	if slowdown >= 1.0 {
+		return f64::INFINITY;
+	}
+
+	if vspeed < speed {
+		speed = vspeed;
+	}
+
+	return edge.length as f64 / f64::max(1.0, speed * (1.0 - slowdown));
+}
+
+pub fn edge_cost_time(edge: &::data::RoutingEdge, constraints: &u8, _: &usize, _: &::data::TMCState) -> f64 {
+	let mut speed = edge.speed as f64;
+	let vspeed = vehicle_speed(*constraints);
+
+	if vspeed < speed {
+		speed = vspeed;
+	}
+
+	return edge.length as f64 / speed;
+}
+
+// the edge between two node ids of the dummy data
+#[cfg(test)]
+fn dummy_edge(data: &::data::RoutingData, from: i64, to: i64) -> usize {
+	(0..data.internal_edges.len()).find(|&e| data.internal_nodes[data.internal_edges[e].source as usize] == from && data.internal_nodes[data.internal_edges[e].target as usize] == to).unwrap()
+}
+
+// a snap at ratio along the edge, 0.0 and 1.0 stand for its nodes
+#[cfg(test)]
+fn edge_endpoint(data: &::data::RoutingData, edge: usize, ratio: f64) -> Endpoint {
+	let (source, target) = (data.position(data.internal_edges[edge].source as usize), data.position(data.internal_edges[edge].target as usize));
+	let position = ::data::Position { lat: source.lat + (target.lat - source.lat) * ratio, lon: source.lon + (target.lon - source.lon) * ratio };
+	Endpoint::Edge(::data::Snap { edge: edge, ratio: ratio, position: position, distance: 0.0, directed: false })
+}
+
+#[test]
+fn test_dijkstra() {
+	let data = ::parser::build_dummy_data();
+	let data = &data.routing_data;
+
+	let tmc_state = RwLock::new(::data::TMCState::new());
+
+	// from node 5000 to node 5003, along the oneway cycle
+	let (first, second) = (dummy_edge(data, 5000, 5002), dummy_edge(data, 5002, 5003));
+	let route = run_dijkstra_between(data, &edge_endpoint(data, first, 0.0), &edge_endpoint(data, second, 1.0), ::data::FLAG_CAR, edge_cost_time, &tmc_state).unwrap();
+
+	assert!((route.distance - 1010.0).abs() < 1e-6);
+	assert!((route.time - 1010.0 / data.internal_edges[first].speed as f64).abs() < 1e-6);
+	assert_eq!(route.edges, vec![first, second]);
+}
+
+#[test]
+fn test_dijkstra_snapped() {
+	let data = ::parser::build_dummy_routing_data();
+
+	let tmc_state = RwLock::new(::data::TMCState::new());
+
+	let edge = |from: i64, to: i64| dummy_edge(&data, from, to);
+	let endpoint = |edge: usize, ratio: f64| edge_endpoint(&data, edge, ratio);
+
+	// same edge in driving direction
+	let route = run_dijkstra_between(&data, &endpoint(edge(5000, 5002), 0.2), &endpoint(edge(5000, 5002), 0.7), ::data::FLAG_CAR, edge_cost_distance, &tmc_state).unwrap();
+	assert!((route.distance - 5.0).abs() < 1e-6);
+
+	// partial first and last edge
+	let route = run_dijkstra_between(&data, &endpoint(edge(5000, 5002), 0.2), &endpoint(edge(5002, 5003), 0.5), ::data::FLAG_CAR, edge_cost_distance, &tmc_state).unwrap();
+	assert!((route.distance - 508.0).abs() < 1e-6);
+	assert_eq!(route.edges, vec![edge(5000, 5002), edge(5002, 5003)]);
+	assert_eq!(route.times.len(), route.path.len());
+	assert!((route.times[route.times.len() - 1] - route.time).abs() < 1e-6);
+
+	// the oneway edge can't be driven backwards, the route has to go around the cycle
+	let route = run_dijkstra_between(&data, &endpoint(edge(5000, 5002), 0.7), &endpoint(edge(5000, 5002), 0.2), ::data::FLAG_CAR, edge_cost_distance, &tmc_state).unwrap();
+	assert!((route.distance - 11005.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_simplify() {
+	// the middle points are less than a meter off the straight line, the corner is kept
+	let mut route = Route { distance: 0.0, time: 0.0, path: vec![[48.0, 9.0], [48.000001, 9.001], [48.0, 9.002], [48.001, 9.002]], times: vec![0.0, 1.0, 2.0, 3.0], edges: Vec::new() };
+	route.simplify(1.0);
+
+	assert_eq!(route.path, vec![[48.0, 9.0], [48.0, 9.002], [48.001, 9.002]]);
+	assert_eq!(route.times, vec![0.0, 2.0, 3.0]);
+}
+
+#[test]
+fn test_route_distances() {
+	let data = ::parser::build_dummy_routing_data();
+	let route_distances = |source: &Endpoint, targets: &[Endpoint], constraints: u8, max_distance: f64| -> Vec<Option<f64>> {
+		let search = bounded_search(&data, source, targets, |edge| data.internal_edges[edge].constraints & constraints != 0, max_distance);
+		(0..targets.len()).map(|i| search.distance(i)).collect()
+	};
+
+	// the nodes 5002, 5003 and 5000 as snaps onto the ends of their edges
+	let (first, second) = (dummy_edge(&data, 5000, 5002), dummy_edge(&data, 5002, 5003));
+	let targets = [edge_endpoint(&data, first, 1.0), edge_endpoint(&data, second, 1.0), edge_endpoint(&data, first, 0.0)];
+
+	let distances = route_distances(&targets[2], &targets, ::data::FLAG_CAR, 5000.0);
+	assert_eq!(distances, vec![Some(10.0), Some(1010.0), Some(0.0)]);
+
+	// 5003 -> 5000 is 10000 long
+	let distances = route_distances(&targets[1], &targets, ::data::FLAG_CAR, 5000.0);
+	assert_eq!(distances, vec![None, Some(0.0), None]);
+
+	// the edges of the paths are kept, including the snapped edges
+	let search = bounded_search(&data, &targets[2], &targets, |_| true, 5000.0);
+	assert_eq!(search.edges(1), Some(vec![first, second]));
+	assert_eq!(search.edges(2), Some(vec![first]));
+}
@@ -0,0 +1,364 @@
+use std::io;
+use std::io::Write;
+use std::collections::BinaryHeap;
+use std::cmp;
+use std::cmp::Ordering;
+use std::f64;
+use ordered_float::OrderedFloat;
+
+use flat::{FlatVec, FlatItem, write_raw};
+
+// maximum number of children per tree node
+pub const NODE_SIZE: usize = 16;
+
+// bounding box in the fixed point coordinates of ::data::RoutingNode
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, RustcEncodable, RustcDecodable)]
+pub struct Rect {
+	pub min_lat: i32,
+	pub min_lon: i32,
+	pub max_lat: i32,
+	pub max_lon: i32,
+}
+
+impl Rect {
+	pub fn empty() -> Rect {
+		Rect { min_lat: i32::max_value(), min_lon: i32::max_value(), max_lat: i32::min_value(), max_lon: i32::min_value() }
+	}
+
+	pub fn from_nodes(a: &::data::RoutingNode, b: &::data::RoutingNode) -> Rect {
+		let mut rect = Rect::empty();
+		rect.extend_node(a);
+		rect.extend_node(b);
+		rect
+	}
+
+	pub fn from_positions(a: &::data::Position, b: &::data::Position) -> Rect {
+		Rect::from_nodes(&::data::RoutingNode::from_position(a), &::data::RoutingNode::from_position(b))
+	}
+
+	pub fn extend_node(&mut self, node: &::data::RoutingNode) {
+		self.extend(&Rect { min_lat: node.lat, min_lon: node.lon, max_lat: node.lat, max_lon: node.lon });
+	}
+
+	pub fn extend(&mut self, other: &Rect) {
+		self.min_lat = self.min_lat.min(other.min_lat);
+		self.min_lon = self.min_lon.min(other.min_lon);
+		self.max_lat = self.max_lat.max(other.max_lat);
+		self.max_lon = self.max_lon.max(other.max_lon);
+	}
+
+	pub fn intersects(&self, other: &Rect) -> bool {
+		self.min_lat <= other.max_lat && other.min_lat <= self.max_lat && self.min_lon <= other.max_lon && other.min_lon <= self.max_lon
+	}
+
+	// meters to the closest point of the box, zero if the position is inside
+	pub fn distance(&self, position: &::data::Position) -> f64 {
+		let node = ::data::RoutingNode::from_position(position);
+		let closest = ::data::RoutingNode { lat: node.lat.max(self.min_lat).min(self.max_lat), lon: node.lon.max(self.min_lon).min(self.max_lon) };
+
+		if closest.lat == node.lat && closest.lon == node.lon {
+			0.0
+		} else {
+			position.distance(&closest.position())
+		}
+	}
+
+	fn center(&self) -> (i64, i64) {
+		((self.min_lat as i64 + self.max_lat as i64) / 2, (self.min_lon as i64 + self.max_lon as i64) / 2)
+	}
+}
+
+unsafe impl FlatItem for Rect {
+	fn write_flat<W: Write>(&self, w: &mut W) -> io::Result<()> {
+		write_raw(&self.min_lat, w)?;
+		write_raw(&self.min_lon, w)?;
+		write_raw(&self.max_lat, w)?;
+		write_raw(&self.max_lon, w)
+	}
+}
+
+// packed hilbert R-tree, items are sorted along a hilbert curve once and the tree is built bottom up
+// from consecutive runs of NODE_SIZE boxes, so it is never modified after the build
+#[derive(Debug, RustcEncodable, RustcDecodable)]
+pub struct RTree {
+	// boxes of all levels, starting with one box per item in sorted order and ending with the root
+	pub boxes: FlatVec<Rect>,
+	// [level] -> index of the first box of that level, one additional entry marks the end
+	pub level_offsets: FlatVec<u32>,
+	// [sorted position] -> item id
+	pub items: FlatVec<u32>,
+}
+
+#[derive(Debug)]
+enum SearchEntry {
+	Node { level: usize, index: usize },
+	Item { id: u32 },
+}
+
+// min heap entry of the nearest neighbour search
+#[derive(Debug)]
+struct HeapEntry {
+	distance: f64,
+	entry: SearchEntry,
+}
+
+impl Ord for HeapEntry {
+	fn cmp(&self, other: &HeapEntry) -> Ordering {
+		OrderedFloat(other.distance).cmp(&OrderedFloat(self.distance))
+	}
+}
+
+impl PartialOrd for HeapEntry {
+	fn partial_cmp(&self, other: &HeapEntry) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Eq for HeapEntry {}
+
+impl PartialEq for HeapEntry {
+	fn eq(&self, other: &HeapEntry) -> bool {
+		OrderedFloat(self.distance) == OrderedFloat(other.distance)
+	}
+}
+
+impl RTree {
+	// the item id is the index into rects
+	pub fn build(rects: &[Rect]) -> RTree {
+		let mut bounds = Rect::empty();
+		for rect in rects {
+			bounds.extend(rect);
+		}
+
+		let mut order: Vec<(u64, u32)> = rects.iter().enumerate().map(|(id, rect)| (hilbert_index(&bounds, rect), id as u32)).collect();
+		order.sort();
+
+		let mut boxes: Vec<Rect> = order.iter().map(|&(_, id)| rects[id as usize]).collect();
+		let items: Vec<u32> = order.iter().map(|&(_, id)| id).collect();
+		let mut level_offsets = vec![0u32];
+
+		let mut level_start = 0;
+		let mut level_end = boxes.len();
+
+		while level_end - level_start > 1 {
+			for chunk_start in (level_start..level_end).step_by(NODE_SIZE) {
+				let mut rect = Rect::empty();
+				for child in &boxes[chunk_start..(chunk_start + NODE_SIZE).min(level_end)] {
+					rect.extend(child);
+				}
+				boxes.push(rect);
+			}
+
+			level_offsets.push(level_end as u32);
+			level_start = level_end;
+			level_end = boxes.len();
+		}
+		level_offsets.push(level_end as u32);
+
+		RTree { boxes: boxes.into(), level_offsets: level_offsets.into(), items: items.into() }
+	}
+
+	pub fn len(&self) -> usize {
+		self.items.len()
+	}
+
+	// item_cnt is the number of items the ids refer to
+	pub fn validate(&self, item_cnt: usize) -> Result<(), String> {
+		if self.level_offsets.first() != Some(&0) || self.level_offsets.last().map(|last| *last as usize) != Some(self.boxes.len()) || !::data::is_monotonic(&self.level_offsets, self.boxes.len()) {
+			return Err("invalid level offsets".to_string());
+		}
+		if self.level_offsets.len() < 2 || self.level_offsets[1] as usize != self.items.len() {
+			return Err("items don't match the first level".to_string());
+		}
+		if self.items.iter().any(|item| *item as usize >= item_cnt) {
+			return Err("invalid item id".to_string());
+		}
+		// searches start at the single root node, every level above the items holds the parents of the one below
+		if !self.items.is_empty() {
+			if self.level_offsets.windows(2).any(|pair| pair[0] >= pair[1]) {
+				return Err("empty tree level".to_string());
+			}
+			let sizes: Vec<usize> = self.level_offsets.windows(2).map(|pair| (pair[1] - pair[0]) as usize).collect();
+			if sizes.last() != Some(&1) || sizes.windows(2).any(|pair| pair[1] != (pair[0] + NODE_SIZE - 1) / NODE_SIZE) {
+				return Err("invalid tree levels".to_string());
+			}
+		}
+		Ok(())
+	}
+
+	pub fn levels(&self) -> usize {
+		self.level_offsets.len() - 1
+	}
+
+	fn level_range(&self, level: usize) -> (usize, usize) {
+		(self.level_offsets[level] as usize, self.level_offsets[level + 1] as usize)
+	}
+
+	// child indices of a node are relative to the start of the level below
+	fn children(&self, level: usize, index: usize) -> (usize, usize) {
+		let (start, end) = self.level_range(level - 1);
+		(index * NODE_SIZE, cmp::min((index + 1) * NODE_SIZE, end - start))
+	}
+
+	fn node_box(&self, level: usize, index: usize) -> &Rect {
+		&self.boxes[self.level_offsets[level] as usize + index]
+	}
+
+	// ids of all items whose box intersects rect
+	pub fn search(&self, rect: &Rect) -> Vec<u32> {
+		let mut result = Vec::new();
+		if self.items.is_empty() {
+			return result;
+		}
+
+		let root_level = self.levels() - 1;
+		let mut stack = vec![(root_level, 0)];
+
+		while let Some((level, index)) = stack.pop() {
+			if !self.node_box(level, index).intersects(rect) {
+				continue;
+			}
+
+			if level == 0 {
+				result.push(self.items[index]);
+			} else {
+				let (start, end) = self.children(level, index);
+				for child in start..end {
+					stack.push((level - 1, child));
+				}
+			}
+		}
+
+		result
+	}
+
+	// up to k items closest to position and within max_distance meters, ordered by distance
+	// item_distance returns the exact distance of an item or None if the item should be skipped,
+	// it must never be smaller than the distance to the item's box
+	pub fn nearest<F>(&self, position: &::data::Position, k: usize, max_distance: f64, mut item_distance: F) -> Vec<(u32, f64)>
+		where F: FnMut(u32) -> Option<f64> {
+		let mut result = Vec::new();
+		if self.items.is_empty() || k == 0 {
+			return result;
+		}
+
+		let root_level = self.levels() - 1;
+		let mut heap = BinaryHeap::new();
+		heap.push(HeapEntry { distance: self.node_box(root_level, 0).distance(position), entry: SearchEntry::Node { level: root_level, index: 0 } });
+
+		while let Some(HeapEntry { distance, entry }) = heap.pop() {
+			if distance > max_distance {
+				break;
+			}
+
+			match entry {
+				SearchEntry::Item { id } => {
+					result.push((id, distance));
+					if result.len() == k {
+						break;
+					}
+				},
+				SearchEntry::Node { level: 0, index } => {
+					let id = self.items[index];
+					if let Some(item_distance) = item_distance(id) {
+						heap.push(HeapEntry { distance: item_distance, entry: SearchEntry::Item { id: id } });
+					}
+				},
+				SearchEntry::Node { level, index } => {
+					let (start, end) = self.children(level, index);
+					for child in start..end {
+						let child_distance = self.node_box(level - 1, child).distance(position);
+						if child_distance <= max_distance {
+							heap.push(HeapEntry { distance: child_distance, entry: SearchEntry::Node { level: level - 1, index: child } });
+						}
+					}
+				}
+			}
+		}
+
+		result
+	}
+}
+
+// position of the box center on a 2^16 x 2^16 hilbert curve over bounds
+fn hilbert_index(bounds: &Rect, rect: &Rect) -> u64 {
+	let (lat, lon) = rect.center();
+	let scale = |value: i64, min: i32, max: i32| if max > min { ((value - min as i64) * 0xffff / (max as i64 - min as i64)) as u64 } else { 0 };
+
+	let mut x = scale(lon, bounds.min_lon, bounds.max_lon);
+	let mut y = scale(lat, bounds.min_lat, bounds.max_lat);
+	let mut d = 0u64;
+
+	let n = 1u64 << 16;
+	let mut s = n / 2;
+	while s > 0 {
+		let rx = if x & s > 0 { 1 } else { 0 };
+		let ry = if y & s > 0 { 1 } else { 0 };
+		d += s * s * ((3 * rx) ^ ry);
+
+		// rotate the quadrant
+		if ry == 0 {
+			if rx == 1 {
+				x = n - 1 - x;
+				y = n - 1 - y;
+			}
+			::std::mem::swap(&mut x, &mut y);
+		}
+		s /= 2;
+	}
+	d
+}
+
+#[test]
+fn test_rtree_queries() {
+	// 40 x 40 points in 0.001 degree steps
+	let positions: Vec<::data::Position> = (0..1600).map(|i| ::data::Position { lat: 48.0 + (i / 40) as f64 * 0.001, lon: 9.0 + (i % 40) as f64 * 0.001 }).collect();
+	let rects: Vec<Rect> = positions.iter().map(|p| Rect::from_positions(p, p)).collect();
+
+	let tree = RTree::build(&rects);
+	assert_eq!(tree.len(), 1600);
+	assert!(tree.levels() >= 3);
+
+	let query = ::data::Position { lat: 48.0123, lon: 9.0271 };
+
+	let mut expected: Vec<(u32, f64)> = positions.iter().enumerate().map(|(id, p)| (id as u32, query.distance(p))).collect();
+	expected.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+	let nearest = tree.nearest(&query, 5, f64::INFINITY, |id| Some(query.distance(&positions[id as usize])));
+	assert_eq!(nearest, expected[0..5].to_vec());
+
+	// radius query
+	let within = tree.nearest(&query, usize::max_value(), 150.0, |id| Some(query.distance(&positions[id as usize])));
+	assert_eq!(within.len(), expected.iter().filter(|e| e.1 <= 150.0).count());
+
+	// filtered items are skipped
+	let nearest = tree.nearest(&query, 1, f64::INFINITY, |id| if id == expected[0].0 { None } else { Some(query.distance(&positions[id as usize])) });
+	assert_eq!(nearest[0].0, expected[1].0);
+
+	let bbox = Rect::from_positions(&::data::Position { lat: 48.0095, lon: 9.0095 }, &::data::Position { lat: 48.0125, lon: 9.0145 });
+	let mut found = tree.search(&bbox);
+	found.sort();
+	let mut expected: Vec<u32> = (0..1600).filter(|&id| rects[id as usize].intersects(&bbox)).collect();
+	expected.sort();
+	assert_eq!(found.len(), 3 * 5);
+	assert_eq!(found, expected);
+
+	assert!(RTree::build(&[]).nearest(&query, 1, f64::INFINITY, |_| Some(0.0)).is_empty());
+
+	// corrupt levels are rejected instead of panicking in the searches
+	assert_eq!(tree.validate(1600), Ok(()));
+	assert_eq!(RTree::build(&[]).validate(0), Ok(()));
+	let with_levels = |level_offsets: Vec<u32>| RTree { boxes: tree.boxes.to_vec().into(), level_offsets: level_offsets.into(), items: tree.items.to_vec().into() };
+	let mut offsets = tree.level_offsets.to_vec();
+	let root = offsets.len() - 2;
+	offsets[root] = offsets[root + 1];
+	assert!(with_levels(offsets).validate(1600).is_err());
+	let mut offsets = tree.level_offsets.to_vec();
+	offsets.insert(1, 1600);
+	assert!(with_levels(offsets).validate(1600).is_err());
+	let mut offsets = tree.level_offsets.to_vec();
+	offsets.pop();
+	*offsets.last_mut().unwrap() = tree.boxes.len() as u32;
+	assert!(with_levels(offsets).validate(1600).is_err());
+}
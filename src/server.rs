@@ -1,30 +1,35 @@
 use std::path::Path;
 use std::sync::Arc;
-use std::collections::BinaryHeap;
 use std::collections::HashMap;
-use std::cmp::Ordering;
-use std::f64;
+use std::collections::BTreeMap;
 use iron::prelude::*;
 use iron::status;
+use iron::mime::Mime;
 use staticfile::Static;
 use mount::Mount;
-use ordered_float::OrderedFloat;
 use urlencoded::UrlEncodedQuery;
 use rustc_serialize::json;
+use rustc_serialize::json::ToJson;
 use time::PreciseTime;
 use std::sync::RwLock;
 use std::str::FromStr;
-
-#[derive(Debug, Clone)]
-struct HeapEntry {
-	node: usize,
-	cost: f64,
-}
+use std::io::Read;
 
 #[derive(Debug, Clone, RustcEncodable, RustcDecodable)]
 struct RoutingResult {
 	duration: i64,
-	route: Option<Route>
+	route: Option<::routing::Route>,
+	source_snap: Option<::routing::SnapInfo>,
+	target_snap: Option<::routing::SnapInfo>
+}
+
+// RoutingResult with the path encoded as polyline
+#[derive(Debug, Clone, RustcEncodable)]
+struct PolylineRoutingResult {
+	duration: i64,
+	route: Option<::format::PolylineRoute>,
+	source_snap: Option<::routing::SnapInfo>,
+	target_snap: Option<::routing::SnapInfo>
 }
 
 #[derive(Debug, Clone, RustcEncodable, RustcDecodable)]
@@ -35,6 +40,12 @@ struct TMCResult {
 #[derive(Debug, Clone, RustcEncodable, RustcDecodable)]
 struct TMCResultEntry {
 	event: String,
+	// cid:tabcd:loc of the primary location
+	location: String,
+	dir: bool,
+	// unix time in seconds
+	expires: i64,
+	starts: Option<i64>,
 	edges: Vec<TMCEdge>
 }
 
@@ -44,66 +55,82 @@ struct TMCEdge {
 	to: [f64; 2]
 }
 
-#[derive(Debug, Clone, RustcEncodable, RustcDecodable)]
-struct Route {
-	distance: f64,
-	time: f64,
-	path: Vec<[f64; 2]>
+#[derive(Debug, Clone, RustcEncodable)]
+struct ReverseResult {
+	road: Option<ReverseRoad>,
+	address: Option<ReverseAddress>
 }
 
-#[derive(Debug, Clone)]
-struct PredecessorInfo {
-	node: usize,
-	edge: usize
+#[derive(Debug, Clone, RustcEncodable)]
+struct ReverseRoad {
+	name: Option<String>,
+	road_ref: Option<String>,
+	position: [f64; 2],
+	distance: f64
 }
 
-impl Ord for HeapEntry {
-	fn cmp(&self, other: &HeapEntry) -> Ordering {
-		OrderedFloat(other.cost).cmp(&OrderedFloat(self.cost))
-	}
+#[derive(Debug, Clone, RustcEncodable)]
+struct ReverseAddress {
+	housenumber: String,
+	street: Option<String>,
+	position: [f64; 2],
+	distance: f64
 }
 
-impl PartialOrd for HeapEntry {
-	fn partial_cmp(&self, other: &HeapEntry) -> Option<Ordering> {
-		Some(self.cmp(other))
-	}
-}
-
-impl Eq for HeapEntry {}
-
-impl PartialEq for HeapEntry {
-	fn eq(&self, other: &HeapEntry) -> bool {
-		return (self.node == other.node) & &(OrderedFloat(other.cost).eq(&OrderedFloat(self.cost)))
-	}
+// maximum number of features returned by /api/edges unless a limit is given
+const EDGE_QUERY_LIMIT: usize = 10000;
+// search radius of /api/reverse in meters unless a radius is given
+const REVERSE_RADIUS: f64 = 200.0;
+
+pub struct ServerConfig {
+	pub bind: String,
+	pub port: u16,
+	pub web_root: String,
+	pub tmc_source: ::tmc_source::SourceConfig,
+	pub tmc_record: Option<String>,
+	// directory of the tmc event list and the language of the event descriptions
+	pub tmc_events: String,
+	pub tmc_language: String,
 }
 
-
-pub fn start(data: ::data::State) {
-	let tmc_state = RwLock::new(::data::TMCState { current_edge_events: HashMap::new(), current_tmc_events: HashMap::new() });
+pub fn start(data: ::data::State, config: &ServerConfig) {
+	let tmc_state = RwLock::new(::data::TMCState::new());
 
 	let data_wrapped = Arc::new(data);
 	let data_wrapped_2 = data_wrapped.clone();
 	let data_wrapped_3 = data_wrapped.clone();
 	let data_wrapped_4 = data_wrapped.clone();
 	let data_wrapped_5 = data_wrapped.clone();
+	let data_wrapped_6 = data_wrapped.clone();
+	let data_wrapped_7 = data_wrapped.clone();
+	let data_wrapped_8 = data_wrapped.clone();
+	let data_wrapped_9 = data_wrapped.clone();
+
+	let edge_tmc = Arc::new(data_wrapped.routing_data.edge_tmc_locations());
+	let edge_tmc_2 = edge_tmc.clone();
 
 	let tmc_state_wrapped = Arc::new(tmc_state);
 	let tmc_state_wrapped_2 = tmc_state_wrapped.clone();
 	let tmc_state_wrapped_3 = tmc_state_wrapped.clone();
+	let tmc_state_wrapped_4 = tmc_state_wrapped.clone();
 
 	let mut mount = Mount::new();
 
-	mount.mount("/", Static::new(Path::new("web/")));
+	mount.mount("/", Static::new(Path::new(&config.web_root)));
 	mount.mount("/api/hello", move |r: &mut Request| get_hello(r, &data_wrapped));
 	mount.mount("/api/graph", move |r: &mut Request| get_graph(r, &data_wrapped_2));
 	mount.mount("/api/route", move |r: &mut Request| get_route(r, &data_wrapped_3, &tmc_state_wrapped));
 	mount.mount("/api/tmc", move |r: &mut Request| get_tmc(r, &data_wrapped_4, &tmc_state_wrapped_2));
+	mount.mount("/api/edges", move |r: &mut Request| get_edges(r, &data_wrapped_6, &edge_tmc));
+	mount.mount("/api/match", move |r: &mut Request| post_match(r, &data_wrapped_8));
+	mount.mount("/api/reverse", move |r: &mut Request| get_reverse(r, &data_wrapped_9));
+	mount.mount("/tiles", move |r: &mut Request| get_tile(r, &data_wrapped_7, &edge_tmc_2, &tmc_state_wrapped_4));
 
-	::tmc::init_tmc_threads(tmc_state_wrapped_3, data_wrapped_5);
+	::tmc::init_tmc_threads(tmc_state_wrapped_3, data_wrapped_5, &config.tmc_source, &config.tmc_record, &config.tmc_events, &config.tmc_language);
 
-	println!("server running on http://localhost:8080/");
+	println!("server running on http://{}:{}/", config.bind, config.port);
 
-	Iron::new(mount).http("127.0.0.1:8080").unwrap();
+	Iron::new(mount).http((config.bind.as_str(), config.port)).unwrap();
 }
 
 fn get_hello(req: &mut Request, data: &::data::State) -> IronResult<Response> {
@@ -117,17 +144,14 @@ fn get_tmc(_: &mut Request, data: &::data::State, tmc_state: &RwLock<::data::TMC
 	let mut result = TMCResult { events: Vec::new() };
 	let tmc = tmc_state.read().unwrap();
 
-	for (_, tmc_value) in &tmc.current_tmc_events {
-		let mut res = TMCResultEntry { event: tmc_value.desc.clone(), edges: Vec::new() };
+	for (tmc_key, tmc_value) in &tmc.current_tmc_events {
+		let mut res = TMCResultEntry { event: tmc_value.desc.clone(), location: tmc_key.loc.to_string(), dir: tmc_key.dir, expires: tmc_value.expires, starts: tmc_value.starts, edges: Vec::new() };
 
-		for edge_id in &tmc_value.edges {
+		for edge_id in tmc_value.edges.keys() {
 			let ref edge = data.routing_data.internal_edges[*edge_id];
 
-			let osm_id_from = data.routing_data.internal_nodes[edge.source];
-			let osm_id_to = data.routing_data.internal_nodes[edge.target];
-
-			let ref pos_from = data.routing_data.osm_nodes.get(&osm_id_from).unwrap().position;
-			let ref pos_to = data.routing_data.osm_nodes.get(&osm_id_to).unwrap().position;
+			let pos_from = data.routing_data.position(edge.source as usize);
+			let pos_to = data.routing_data.position(edge.target as usize);
 
 			res.edges.push(TMCEdge { from: [pos_from.lat, pos_from.lon], to: [pos_to.lat, pos_to.lon] });
 		}
@@ -143,209 +167,266 @@ fn get_graph(req: &mut Request, data: &::data::State) -> IronResult<Response> {
 	Ok(Response::with((status::Ok, format!("nodes: {}, edges: {}", data.routing_data.internal_nodes.len(), data.routing_data.internal_edges.len()))))
 }
 
-fn parse_position(raw: &str) -> Option<::data::Position> {
-	let mut split = raw.split(",");
+// bbox is given as min_lon,min_lat,max_lon,max_lat
+fn parse_bbox(raw: &str) -> Option<::rtree::Rect> {
+	let values: Vec<f64> = raw.split(",").filter_map(|value| value.parse::<f64>().ok()).collect();
 
-	if let (Some(lat_str), Some(lon_str)) = (split.next(), split.next()) {
-		if let (Ok(lat), Ok(lon)) = (lat_str.parse::<f64>(), lon_str.parse::<f64>()) {
-			return Some(::data::Position { lat: lat, lon: lon });
-		}
+	if values.len() != 4 {
+		return None;
 	}
 
-	None
+	Some(::rtree::Rect::from_positions(&::data::Position { lat: values[1], lon: values[0] }, &::data::Position { lat: values[3], lon: values[2] }))
 }
 
-fn get_route(req: &mut Request, data: &::data::State, tmc_state: &RwLock<::data::TMCState>) -> IronResult<Response> {
-	if let Ok(ref query_map) = req.get_ref::<UrlEncodedQuery>() {
-		let source_raw = query_map.get("source").and_then(|list| list.first()).and_then(|string| Some(string.as_str())).unwrap_or("49.51807644873301,10.689697265625");
-		let target_raw = query_map.get("target").and_then(|list| list.first()).and_then(|string| Some(string.as_str())).unwrap_or("48.30877444352327,10.12939453125");
-		let metric_raw = query_map.get("metric").and_then(|list| list.first()).and_then(|string| Some(string.as_str())).unwrap_or("time");
-		let vehicle_raw = query_map.get("vehicle").and_then(|list| list.first()).and_then(|string| Some(string.as_str())).unwrap_or("car");
-		let use_tmc_raw = query_map.get("tmc").and_then(|list| list.first()).and_then(|string| Some(string.as_str())).unwrap_or("false");
-
-		let source_pos = parse_position(source_raw).unwrap_or(::data::Position { lat: 49.51807644873301, lon: 10.689697265625 });
-		let target_pos = parse_position(target_raw).unwrap_or(::data::Position { lat: 8.30877444352327, lon: 10.12939453125 });
-
-		let source = data.grid.find_closest_node(&source_pos, &data.routing_data);
-		let target = data.grid.find_closest_node(&target_pos, &data.routing_data);
+// edges whose bounding box intersects bbox, or which pass within radius meters of center, as geojson
+fn get_edges(req: &mut Request, data: &::data::State, edge_tmc: &HashMap<usize, Vec<::data::TMCLocation>>) -> IronResult<Response> {
+	println!("Running get_edges handler");
 
-		let use_tmc = bool::from_str(use_tmc_raw).unwrap_or(false);
-
-		let vehice = match vehicle_raw {
-			"car" => ::data::FLAG_CAR,
-			"bike" => ::data::FLAG_BIKE,
-			"walk" => ::data::FLAG_WALK,
-			_ => ::data::FLAG_CAR
-		};
-
-		let metric = match (metric_raw, use_tmc) {
-			("time", true) => edge_cost_tmc,
-			("time", false) => edge_cost_time,
-			("distance", _) => edge_cost_distance,
-			_ => edge_cost_distance
-		};
-
-		println!("doing routing from {} to {} for vehicle {} with metric {} and tmc {}", source, target, vehicle_raw, metric_raw, use_tmc);
+	let query_map = match req.get_ref::<UrlEncodedQuery>() {
+		Ok(query_map) => query_map,
+		Err(_) => return Ok(Response::with((status::BadRequest, "expected bbox or center and radius"))),
+	};
+	let param = |name: &str| query_map.get(name).and_then(|list| list.first()).map(|string| string.as_str());
+
+	let bbox = param("bbox").and_then(parse_bbox);
+	let center = param("center").and_then(::routing::parse_position);
+	let radius = param("radius").and_then(|radius| radius.parse::<f64>().ok());
+	let limit = param("limit").and_then(|limit| limit.parse::<usize>().ok()).unwrap_or(EDGE_QUERY_LIMIT);
+
+	// bbox results are limited by id, radius results keep the edges closest to the center
+	let mut edges = match (bbox, center, radius) {
+		(Some(rect), _, _) => {
+			let mut edges = data.edge_index.search(&rect);
+			edges.sort();
+			edges.truncate(limit);
+			edges
+		},
+		(None, Some(center), Some(radius)) => {
+			data.edge_index.nearest(&center, limit, radius, |edge| Some(center.distance(&data.routing_data.project_onto_edge(edge as usize, &center).1)))
+				.into_iter().map(|(edge, _)| edge).collect()
+		},
+		_ => return Ok(Response::with((status::BadRequest, "expected bbox or center and radius"))),
+	};
+	edges.sort();
+
+	let features = edges.iter().map(|&edge_id| {
+		let ref edge = data.routing_data.internal_edges[edge_id as usize];
+		let mut properties = BTreeMap::new();
+
+		properties.insert("id".to_string(), edge_id.to_json());
+		properties.insert("source".to_string(), data.routing_data.internal_nodes[edge.source as usize].to_json());
+		properties.insert("target".to_string(), data.routing_data.internal_nodes[edge.target as usize].to_json());
+		properties.insert("length".to_string(), (edge.length as f64).to_json());
+		properties.insert("speed_kmh".to_string(), (edge.speed as f64 * 3.6).round().to_json());
+		properties.insert("constraints".to_string(), edge.constraints.to_json());
+		properties.insert("car".to_string(), (edge.constraints & ::data::FLAG_CAR != 0).to_json());
+		properties.insert("bike".to_string(), (edge.constraints & ::data::FLAG_BIKE != 0).to_json());
+		properties.insert("walk".to_string(), (edge.constraints & ::data::FLAG_WALK != 0).to_json());
+		properties.insert("tmc".to_string(), edge_tmc.get(&(edge_id as usize)).map(|locations| locations.iter().map(|location| location.to_string()).collect::<Vec<String>>()).unwrap_or(Vec::new()).to_json());
+
+		let line = [data.routing_data.position(edge.source as usize), data.routing_data.position(edge.target as usize)];
+		::geojson::feature(::geojson::line_string(&line), properties)
+	}).collect();
+
+	Ok(Response::with((status::Ok, ::geojson::feature_collection(features).to_string())))
+}
 
-		let start = PreciseTime::now();
-		let result = run_dijkstra(&data.routing_data, source, target, vehice, metric, tmc_state);
-		let end = PreciseTime::now();
-		//println!("route: {:?}", result);
+#[derive(Debug, Clone, RustcEncodable)]
+struct MatchResponse {
+	duration: i64,
+	result: ::matching::MatchResult
+}
 
-		let result = RoutingResult { duration: start.to(end).num_milliseconds(), route: result };
+// the request body is a gpx file or a json list of points
+fn post_match(req: &mut Request, data: &::data::State) -> IronResult<Response> {
+	println!("Running post_match handler");
 
-		Ok(Response::with((status::Ok, json::encode(&result).unwrap())))
-	} else {
-		Ok(Response::with((status::InternalServerError)))
+	let mut body = String::new();
+	if req.body.read_to_string(&mut body).is_err() {
+		return Ok(Response::with((status::BadRequest, "unreadable request body")));
 	}
-}
 
-fn run_dijkstra<F>(data: &::data::RoutingData, source_osm: i64, target_osm: i64, constraints: u8, cost_func: F, tmc_state: &RwLock<::data::TMCState>) -> Option<Route>
-	where F: Fn(&::data::RoutingEdge, &f64, &usize, &::data::TMCState) -> f64 {
-	let vspeed = match constraints {
-		::data::FLAG_CAR => 130.0 / 3.6,
-		::data::FLAG_BIKE => 15.0 / 3.6,
-		::data::FLAG_WALK => 5.0 / 3.6,
-		_ => 130.0 / 3.6
+	let (vehicle, radius) = match req.get_ref::<UrlEncodedQuery>() {
+		Ok(query_map) => (
+			query_map.get("vehicle").and_then(|list| list.first()).map(|vehicle| ::routing::vehicle_constraints(vehicle)),
+			query_map.get("radius").and_then(|list| list.first()).and_then(|radius| radius.parse::<f64>().ok())
+		),
+		Err(_) => (None, None),
 	};
 
-	let mut distance = vec![f64::INFINITY; data.internal_nodes.len()];
-	let mut predecessor = vec![0; data.internal_nodes.len()];
-	let mut predecessor_edge = vec![0; data.internal_nodes.len()];
-
-	let source = data.osm_nodes.get(&source_osm).unwrap().internal_id;
-	let target = data.osm_nodes.get(&target_osm).unwrap().internal_id;
-
-	let tmc = tmc_state.read().unwrap();
-
-	let mut heap = BinaryHeap::new();
-
-	distance[source] = 0.0;
-	heap.push(HeapEntry { node: source, cost: 0.0 });
+	let trace = if body.trim_start().starts_with("<") { ::matching::parse_gpx(&body) } else { ::matching::parse_json(&body) };
+	let trace = match trace {
+		Ok(trace) => trace,
+		Err(e) => return Ok(Response::with((status::BadRequest, format!("invalid trace: {}", e)))),
+	};
 
-	println!("begin dijkstra");
+	let mut config = ::matching::MatchConfig::new(vehicle.unwrap_or(::data::FLAG_CAR));
+	if let Some(radius) = radius {
+		config.radius = radius;
+	}
 
-	while let Some(HeapEntry { node, cost }) = heap.pop() {
-		if node == target {
-			println!("found route");
-			return build_route(source, target, &predecessor, &predecessor_edge, &data, &vspeed);
-		}
+	let start = PreciseTime::now();
+	let result = ::matching::match_trace(data, &trace, &config);
+	let end = PreciseTime::now();
 
-		if cost > distance[node] { continue; }
+	println!("matched {} points into {} parts in {}", trace.len(), result.matchings.len(), start.to(end));
 
-		let (start, end) = offset_lookup(&node, &data);
-		let edges = &data.internal_edges[start..end];
+	let response = MatchResponse { duration: start.to(end).num_milliseconds(), result: result };
+	Ok(Response::with((status::Ok, json::encode(&response).unwrap())))
+}
 
-		for (i, edge) in edges.iter().enumerate() {
-			if constraints & edge.constraints == 0 {
-				continue;
-			}
+// nearest named road and nearest address, addresses without street get the road's name
+fn get_reverse(req: &mut Request, data: &::data::State) -> IronResult<Response> {
+	println!("Running get_reverse handler");
 
-			let neighbor = HeapEntry { node: edge.target, cost: cost + cost_func(&edge, &vspeed, &(i + start), &tmc) };
+	let query_map = match req.get_ref::<UrlEncodedQuery>() {
+		Ok(query_map) => query_map,
+		Err(_) => return Ok(Response::with((status::BadRequest, "expected lat and lon"))),
+	};
+	let param = |name: &str| query_map.get(name).and_then(|list| list.first()).and_then(|value| value.parse::<f64>().ok());
 
-			if neighbor.cost < distance[neighbor.node] {
-				distance[edge.target] = neighbor.cost;
-				predecessor[edge.target] = node;
-				predecessor_edge[edge.target] = i + start;
-				heap.push(neighbor);
-			}
+	let position = match (param("lat"), param("lon")) {
+		(Some(lat), Some(lon)) => ::data::Position { lat: lat, lon: lon },
+		_ => return Ok(Response::with((status::BadRequest, "expected lat and lon"))),
+	};
+	let radius = param("radius").unwrap_or(REVERSE_RADIUS);
+
+	let road = data.find_closest_named_edge(&position, radius).map(|snap| ReverseRoad {
+		name: data.names.get(data.routing_data.edge_names[snap.edge]).map(|name| name.to_string()),
+		road_ref: data.names.get(data.routing_data.edge_refs[snap.edge]).map(|road_ref| road_ref.to_string()),
+		position: [snap.position.lat, snap.position.lon],
+		distance: snap.distance
+	});
+
+	let address = data.find_closest_address(&position, radius).map(|(id, distance)| {
+		let ref address = data.addresses[id];
+		let address_position = address.position.position();
+
+		ReverseAddress {
+			housenumber: data.names.get(address.housenumber).unwrap_or("").to_string(),
+			street: data.names.get(address.street).map(|street| street.to_string()).or(road.as_ref().and_then(|road| road.name.clone())),
+			position: [address_position.lat, address_position.lon],
+			distance: distance
 		}
-	}
-	println!("no route found");
-	return None;
+	});
+
+	Ok(Response::with((status::Ok, json::encode(&ReverseResult { road: road, address: address }).unwrap())))
 }
 
+// /tiles/{z}/{x}/{y}.mvt
+fn get_tile(req: &mut Request, data: &::data::State, edge_tmc: &HashMap<usize, Vec<::data::TMCLocation>>, tmc_state: &RwLock<::data::TMCState>) -> IronResult<Response> {
+	let path = req.url.path();
 
-fn offset_lookup(node: &usize, data: &::data::RoutingData) -> (usize, usize) {
-	let start = data.internal_offset[*node];
-	let next_node = node + 1;
-	let max_end = data.internal_offset[data.internal_offset.len() - 1];
+	let tile = match (path.get(0), path.get(1), path.get(2).map(|y| y.trim_end_matches(".mvt"))) {
+		(Some(z), Some(x), Some(y)) => (z.parse::<u32>(), x.parse::<u32>(), y.parse::<u32>()),
+		_ => return Ok(Response::with((status::NotFound))),
+	};
 
-	if next_node > data.internal_offset.len() - 1 {
-		assert!(start <= max_end, "invalid offset lookup max!");
+	match tile {
+		(Ok(z), Ok(x), Ok(y)) if ::mvt::valid_tile(z, x, y) => {
+			let tmc = tmc_state.read().unwrap();
+			let encoded = ::mvt::encode_edge_tile(data, edge_tmc, &tmc, z, x, y);
 
-		return (start, max_end);
+			let content_type: Mime = "application/vnd.mapbox-vector-tile".parse().unwrap();
+			Ok(Response::with((content_type, status::Ok, encoded)))
+		},
+		_ => Ok(Response::with((status::NotFound))),
 	}
-
-	let end = data.internal_offset[next_node];
-
-	assert!(start <= end, "invalid offset lookup!");
-
-	return (start, end);
 }
 
+fn get_route(req: &mut Request, data: &::data::State, tmc_state: &RwLock<::data::TMCState>) -> IronResult<Response> {
+	if let Ok(ref query_map) = req.get_ref::<UrlEncodedQuery>() {
+		let source_raw = query_map.get("source").and_then(|list| list.first()).and_then(|string| Some(string.as_str())).unwrap_or("49.51807644873301,10.689697265625");
+		let target_raw = query_map.get("target").and_then(|list| list.first()).and_then(|string| Some(string.as_str())).unwrap_or("48.30877444352327,10.12939453125");
+		let metric_raw = query_map.get("metric").and_then(|list| list.first()).and_then(|string| Some(string.as_str())).unwrap_or("time");
+		let vehicle_raw = query_map.get("vehicle").and_then(|list| list.first()).and_then(|string| Some(string.as_str())).unwrap_or("car");
+		let use_tmc_raw = query_map.get("tmc").and_then(|list| list.first()).and_then(|string| Some(string.as_str())).unwrap_or("false");
+		let format_raw = query_map.get("format").and_then(|list| list.first()).and_then(|string| Some(string.as_str())).unwrap_or("json");
 
-fn build_route(source: usize, target: usize, predecessor: &Vec<usize>, predecessor_edge: &Vec<usize>, data: &::data::RoutingData, vspeed: &f64) -> Option<Route> {
-	let mut result = Route { distance: 0.0, time: 0.0, path: Vec::new() };
-
-	let mut node = target;
-	let mut edge = predecessor_edge[node];
-
-	loop {
-		if node == source {
-			break;
-		}
-
-		let osm_id = data.internal_nodes[node];
-		let ref pos = data.osm_nodes.get(&osm_id).unwrap().position;
-
-		let mut speed = data.internal_edges[edge].speed;
+		// douglas-peucker tolerance in meters
+		let simplify = query_map.get("simplify").and_then(|list| list.first()).and_then(|tolerance| tolerance.parse::<f64>().ok());
 
-		if *vspeed < speed {
-			speed = *vspeed;
-		}
+		let format = match ::format::RouteFormat::parse(format_raw) {
+			Some(format) => format,
+			None => return Ok(Response::with((status::BadRequest, format!("unknown format {}, expected json, geojson, gpx, polyline5 or polyline6", format_raw)))),
+		};
 
-		result.path.push([pos.lat, pos.lon]);
-		result.distance += data.internal_edges[edge].length;
-		result.time += data.internal_edges[edge].length / speed;
+		// optional snapping restrictions: radius in meters, bearings as "degrees" or "degrees,range"
+		let radius_raw = query_map.get("radius").and_then(|list| list.first());
+		let source_bearing_raw = query_map.get("source_bearing").and_then(|list| list.first());
+		let target_bearing_raw = query_map.get("target_bearing").and_then(|list| list.first());
 
-		node = predecessor[node];
-		edge = predecessor_edge[node];
-	}
+		let source_pos = ::routing::parse_position(source_raw).unwrap_or(::data::Position { lat: 49.51807644873301, lon: 10.689697265625 });
+		let target_pos = ::routing::parse_position(target_raw).unwrap_or(::data::Position { lat: 8.30877444352327, lon: 10.12939453125 });
 
-	result.path.reverse();
+		let use_tmc = bool::from_str(use_tmc_raw).unwrap_or(false);
 
-	println!("build path, dist: {}, time: {}", result.distance, result.time);
+		let vehice = ::routing::vehicle_constraints(vehicle_raw);
+		let metric = ::routing::cost_function(metric_raw, use_tmc);
 
-	return Some(result);
-}
+		let mut snap_query = ::data::SnapQuery::new(vehice);
+		snap_query.radius = radius_raw.and_then(|radius| radius.parse::<f64>().ok());
 
-fn edge_cost_distance(edge: &::data::RoutingEdge, _: &f64, _: &usize, _: &::data::TMCState) -> f64 {
-	return edge.length;
-}
+		let source_query = ::data::SnapQuery { bearing: source_bearing_raw.and_then(|bearing| ::routing::parse_bearing(bearing)), ..snap_query };
+		let target_query = ::data::SnapQuery { bearing: target_bearing_raw.and_then(|bearing| ::routing::parse_bearing(bearing)), ..snap_query };
 
-fn edge_cost_tmc(edge: &::data::RoutingEdge, vspeed: &f64, edge_id: &usize, state: &::data::TMCState) -> f64 {
-	let mut speed = edge.speed;
+		let source_snap = data.find_closest_edge(&source_pos, &source_query);
+		let target_snap = data.find_closest_edge(&target_pos, &target_query);
 
-	let slowdown = match state.current_edge_events.get(edge_id) {
-		Some(tmc_event) => {
-			*tmc_event
-		},
-		None => 0.0,
-	};
+		let start = PreciseTime::now();
+		let result = match (source_snap, target_snap) {
+			(Some(source), Some(target)) => {
+				println!("doing routing from edge {} to edge {} for vehicle {} with metric {} and tmc {}", source.edge, target.edge, vehicle_raw, metric_raw, use_tmc);
+				::routing::run_dijkstra_between(&data.routing_data, &::routing::Endpoint::Edge(source), &::routing::Endpoint::Edge(target), vehice, metric, tmc_state)
+			},
+			_ => {
+				println!("no edge usable by {} found near source or target", vehicle_raw);
+				None
+			}
+		};
+		let end = PreciseTime::now();
+		//println!("route: {:?}", result);
 
-	if *vspeed < speed {
-		speed = *vspeed;
-	}
+		let result = match (result, simplify) {
+			(Some(mut route), Some(tolerance)) => {
+				route.simplify(tolerance);
+				Some(route)
+			},
+			(result, _) => result,
+		};
 
-	return edge.length / f64::max(1.0, speed * (1.0 - slowdown));
-}
+		let result = RoutingResult {
+			duration: start.to(end).num_milliseconds(),
+			route: result,
+			source_snap: source_snap.map(|snap| ::routing::SnapInfo::from_snap(&snap)),
+			target_snap: target_snap.map(|snap| ::routing::SnapInfo::from_snap(&snap))
+		};
 
-fn edge_cost_time(edge: &::data::RoutingEdge, vspeed: &f64, _: &usize, _: &::data::TMCState) -> f64 {
-	let mut speed = edge.speed;
+		let content_type: Mime = format.content_type().parse().unwrap();
+		let body = match format {
+			::format::RouteFormat::Json => json::encode(&result).unwrap(),
+			::format::RouteFormat::GeoJson => {
+				let mut properties = BTreeMap::new();
+				properties.insert("duration".to_string(), result.duration.to_json());
+				properties.insert("source_snap_distance".to_string(), result.source_snap.as_ref().map(|snap| snap.distance).to_json());
+				properties.insert("target_snap_distance".to_string(), result.target_snap.as_ref().map(|snap| snap.distance).to_json());
+				::format::route_collection(result.route.as_ref(), properties).to_string()
+			},
+			::format::RouteFormat::Gpx => ::format::route_to_gpx(result.route.as_ref(), ::time::get_time()),
+			::format::RouteFormat::Polyline(precision) => {
+				let encoded = PolylineRoutingResult {
+					duration: result.duration,
+					route: result.route.as_ref().map(|route| ::format::PolylineRoute::from_route(route, precision)),
+					source_snap: result.source_snap,
+					target_snap: result.target_snap
+				};
+				json::encode(&encoded).unwrap()
+			},
+		};
 
-	if *vspeed < speed {
-		speed = *vspeed;
+		Ok(Response::with((content_type, status::Ok, body)))
+	} else {
+		Ok(Response::with((status::InternalServerError)))
 	}
-
-	return edge.length / speed;
 }
 
-#[test]
-fn test_dijkstra() {
-	let data = ::parser::build_dummy_data();
-
-	let path = run_dijkstra(&data, 5000, 5003, ::data::FLAG_CAR, edge_cost_time);
-
-	println!("path: {:?}", path);
-}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use std::str;
+
+use flat::FlatVec;
+
+// id of a missing string
+pub const NONE: u32 = ::std::u32::MAX;
+
+// all strings concatenated into one byte buffer, equal strings are stored once
+#[derive(Debug, RustcEncodable, RustcDecodable)]
+pub struct StringTable {
+	pub bytes: FlatVec<u8>,
+	// [string id] -> start in bytes, one additional entry marks the end
+	pub offsets: FlatVec<u32>,
+}
+
+impl StringTable {
+	pub fn new() -> StringTable {
+		StringTable { bytes: FlatVec::new(), offsets: vec![0u32].into() }
+	}
+
+	pub fn len(&self) -> usize {
+		self.offsets.len() - 1
+	}
+
+	pub fn validate(&self) -> Result<(), String> {
+		if self.offsets.is_empty() || !::data::is_monotonic(&self.offsets, self.bytes.len()) {
+			return Err("invalid string offsets".to_string());
+		}
+		Ok(())
+	}
+
+	pub fn get(&self, id: u32) -> Option<&str> {
+		if id == NONE || id as usize >= self.len() {
+			return None;
+		}
+		let (start, end) = (self.offsets[id as usize] as usize, self.offsets[id as usize + 1] as usize);
+		str::from_utf8(&self.bytes[start..end]).ok()
+	}
+}
+
+// only used during the parse, deduplicates strings while filling the table
+pub struct StringTableBuilder {
+	pub table: StringTable,
+	ids: HashMap<String, u32>,
+}
+
+impl StringTableBuilder {
+	pub fn new() -> StringTableBuilder {
+		StringTableBuilder { table: StringTable::new(), ids: HashMap::new() }
+	}
+
+	pub fn add(&mut self, value: &str) -> u32 {
+		if let Some(id) = self.ids.get(value) {
+			return *id;
+		}
+
+		let id = self.table.len() as u32;
+		for byte in value.as_bytes() {
+			self.table.bytes.push(*byte);
+		}
+		let end = self.table.bytes.len() as u32;
+		self.table.offsets.push(end);
+		self.ids.insert(value.to_string(), id);
+		id
+	}
+
+	// NONE for missing values
+	pub fn add_option(&mut self, value: Option<&String>) -> u32 {
+		match value {
+			Some(value) if !value.is_empty() => self.add(value),
+			_ => NONE,
+		}
+	}
+}
+
+#[test]
+fn test_string_table() {
+	let mut builder = StringTableBuilder::new();
+	let main_street = builder.add("Hauptstraße");
+	let b27 = builder.add("B 27");
+	assert_eq!(builder.add("Hauptstraße"), main_street);
+	assert_eq!(builder.add_option(None), NONE);
+	assert_eq!(builder.add_option(Some(&String::new())), NONE);
+
+	let table = builder.table;
+	assert_eq!(table.len(), 2);
+	assert_eq!(table.get(main_street), Some("Hauptstraße"));
+	assert_eq!(table.get(b27), Some("B 27"));
+	assert_eq!(table.get(NONE), None);
+	assert_eq!(table.get(2), None);
+}
@@ -0,0 +1,307 @@
+use std::process::{Command, Stdio, Child, ChildStdout};
+use std::io;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::fs::{File, OpenOptions};
+use std::net::{TcpStream, UdpSocket};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use std::thread;
+
+pub const SAMPLE_EVENTS: &'static str = "rds/sample_events.log";
+
+// where the tmc listener reads receiver lines (`GF evt=.. loc=..`, `GS CI=..`) from
+#[derive(Debug, Clone, PartialEq)]
+pub enum SourceConfig {
+	// spawns rdsd and reads rdsquery's output, needs the rds dongle
+	Rdsd,
+	File(String),
+	// a recording, lines are delayed by their recorded time divided by speed, speed 0 doesn't wait but keeps the offsets
+	Replay { path: String, speed: f64 },
+	// connects to a receiver sending one line per message
+	Tcp(String),
+	// binds to the address, every datagram contains one or more lines
+	Udp(String),
+	Stdin,
+	// raw hex rds groups read from another source, decoded into receiver lines
+	Rds(Box<SourceConfig>),
+	None,
+}
+
+impl SourceConfig {
+	// rdsd, sample, file:<path>, replay:<path>, tcp:<host:port>, udp:<host:port>, stdin, rds:<source> or none
+	pub fn parse(spec: &str) -> Result<SourceConfig, String> {
+		let (kind, argument) = match spec.find(':') {
+			Some(index) => (&spec[..index], Some(&spec[index + 1..])),
+			None => (spec, None),
+		};
+
+		match (kind, argument) {
+			("rdsd", None) => Ok(SourceConfig::Rdsd),
+			("sample", None) => Ok(SourceConfig::File(SAMPLE_EVENTS.to_string())),
+			("file", Some(path)) if !path.is_empty() => Ok(SourceConfig::File(path.to_string())),
+			("replay", Some(path)) if !path.is_empty() => Ok(SourceConfig::Replay { path: path.to_string(), speed: 1.0 }),
+			("tcp", Some(address)) if !address.is_empty() => Ok(SourceConfig::Tcp(address.to_string())),
+			("udp", Some(address)) if !address.is_empty() => Ok(SourceConfig::Udp(address.to_string())),
+			("stdin", None) => Ok(SourceConfig::Stdin),
+			("rds", Some(inner)) => Ok(SourceConfig::Rds(Box::new(SourceConfig::parse(inner)?))),
+			("none", None) => Ok(SourceConfig::None),
+			_ => Err(format!("unknown tmc source: {}", spec)),
+		}
+	}
+
+	pub fn open(&self) -> io::Result<Box<dyn TMCSource>> {
+		match *self {
+			SourceConfig::Rdsd => Ok(Box::new(RdsdSource::spawn()?)),
+			SourceConfig::File(ref path) => Ok(Box::new(ReaderSource { name: format!("file {}", path), reader: BufReader::new(File::open(path)?) })),
+			SourceConfig::Replay { ref path, speed } => Ok(Box::new(ReplaySource { name: format!("replay {} ({}x)", path, speed), reader: BufReader::new(File::open(path)?), speed: speed, start: None, received: None })),
+			SourceConfig::Tcp(ref address) => Ok(Box::new(ReaderSource { name: format!("tcp {}", address), reader: BufReader::new(TcpStream::connect(address.as_str())?) })),
+			SourceConfig::Udp(ref address) => Ok(Box::new(UdpSource { socket: UdpSocket::bind(address.as_str())?, pending: VecDeque::new() })),
+			SourceConfig::Stdin => Ok(Box::new(ReaderSource { name: "stdin".to_string(), reader: BufReader::new(io::stdin()) })),
+			SourceConfig::Rds(ref inner) => Ok(Box::new(::rds::RdsSource::new(inner.open()?))),
+			SourceConfig::None => Err(io::Error::new(io::ErrorKind::NotFound, "no tmc source configured")),
+		}
+	}
+}
+
+pub trait TMCSource: Send {
+	fn name(&self) -> String;
+
+	// the next line without line break, None once the source is exhausted
+	fn next_line(&mut self) -> Option<String>;
+
+	// unix time in seconds the last line was received at, recordings return their recorded time
+	fn received(&self) -> i64 {
+		::time::get_time().sec
+	}
+}
+
+// files, tcp streams and stdin
+struct ReaderSource<R: io::Read + Send> {
+	name: String,
+	reader: BufReader<R>,
+}
+
+impl<R: io::Read + Send> TMCSource for ReaderSource<R> {
+	fn name(&self) -> String {
+		self.name.clone()
+	}
+
+	fn next_line(&mut self) -> Option<String> {
+		read_line(&mut self.reader)
+	}
+}
+
+fn read_line<B: BufRead>(reader: &mut B) -> Option<String> {
+	let mut line = String::new();
+	match reader.read_line(&mut line) {
+		Ok(0) | Err(_) => None,
+		Ok(_) => Some(line.trim_end_matches(|c| c == '\n' || c == '\r').to_string()),
+	}
+}
+
+// recordings contain one received line per line, prefixed by the unix time in milliseconds:
+// 1467367200123 GF evt=803 loc=45112 ext=1 CI=5 dir=0
+pub struct Recorder {
+	writer: BufWriter<File>,
+}
+
+impl Recorder {
+	// appends to existing recordings
+	pub fn open(path: &str) -> io::Result<Recorder> {
+		let file = OpenOptions::new().create(true).append(true).open(path)?;
+		Ok(Recorder { writer: BufWriter::new(file) })
+	}
+
+	pub fn record(&mut self, line: &str) -> io::Result<()> {
+		let now = ::time::get_time();
+		self.record_at(now.sec * 1000 + now.nsec as i64 / 1000000, line)
+	}
+
+	// flushed after every line, so recordings survive a killed server
+	fn record_at(&mut self, millis: i64, line: &str) -> io::Result<()> {
+		writeln!(self.writer, "{} {}", millis, line)?;
+		self.writer.flush()
+	}
+}
+
+// recorded time and the received line
+fn parse_recorded_line(line: &str) -> Option<(i64, &str)> {
+	let index = line.find(' ')?;
+	let millis = line[..index].parse::<i64>().ok()?;
+	Some((millis, &line[index + 1..]))
+}
+
+struct ReplaySource {
+	name: String,
+	reader: BufReader<File>,
+	speed: f64,
+	// recorded time of the first line, when it was replayed and the unix time in milliseconds of that
+	start: Option<(i64, Instant, i64)>,
+	// recorded time of the last line, shifted to the time of the replay
+	received: Option<i64>,
+}
+
+impl TMCSource for ReplaySource {
+	fn name(&self) -> String {
+		self.name.clone()
+	}
+
+	// lines without time (plain logs) are replayed at once. lines with time are received at the start of the replay plus
+	// their recorded offset divided by speed, speed 0 doesn't wait and keeps the offsets as recorded
+	fn next_line(&mut self) -> Option<String> {
+		let line = read_line(&mut self.reader)?;
+
+		let (millis, message) = match parse_recorded_line(&line) {
+			Some((millis, message)) => (millis, message.to_string()),
+			None => {
+				self.received = None;
+				return Some(line);
+			}
+		};
+
+		let now = ::time::get_time();
+		let (first, started, replayed) = *self.start.get_or_insert((millis, Instant::now(), now.sec * 1000 + now.nsec as i64 / 1000000));
+		let speed = if self.speed > 0.0 { self.speed } else { 1.0 };
+		if self.speed > 0.0 {
+			let due = Duration::from_millis(((millis - first).max(0) as f64 / speed) as u64);
+			let elapsed = started.elapsed();
+			if due > elapsed {
+				thread::sleep(due - elapsed);
+			}
+		}
+		self.received = Some((replayed + ((millis - first) as f64 / speed) as i64) / 1000);
+
+		Some(message)
+	}
+
+	fn received(&self) -> i64 {
+		self.received.unwrap_or(::time::get_time().sec)
+	}
+}
+
+struct RdsdSource {
+	rdsd: Child,
+	rdsquery: Child,
+	reader: BufReader<ChildStdout>,
+}
+
+impl RdsdSource {
+	fn spawn() -> io::Result<RdsdSource> {
+		let mut rdsd = Command::new("rdsd").stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null()).spawn()?;
+		let rdsquery = Command::new("rdsquery").arg("-s").arg("localhost").arg("-c").arg("0").arg("-t").arg("tmc").stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::null()).spawn();
+
+		let mut rdsquery = match rdsquery {
+			Ok(rdsquery) => rdsquery,
+			Err(e) => {
+				let _ = rdsd.kill();
+				return Err(e);
+			}
+		};
+
+		println!("[RDSD] successfully spawned rdsd ({}) and rdsquery ({})", rdsd.id(), rdsquery.id());
+		let reader = BufReader::new(rdsquery.stdout.take().unwrap());
+		Ok(RdsdSource { rdsd: rdsd, rdsquery: rdsquery, reader: reader })
+	}
+}
+
+impl TMCSource for RdsdSource {
+	fn name(&self) -> String {
+		"rdsd".to_string()
+	}
+
+	fn next_line(&mut self) -> Option<String> {
+		read_line(&mut self.reader)
+	}
+}
+
+impl Drop for RdsdSource {
+	fn drop(&mut self) {
+		let _ = self.rdsquery.kill();
+		let _ = self.rdsd.kill();
+	}
+}
+
+struct UdpSource {
+	socket: UdpSocket,
+	pending: VecDeque<String>,
+}
+
+impl TMCSource for UdpSource {
+	fn name(&self) -> String {
+		format!("udp {}", self.socket.local_addr().map(|address| address.to_string()).unwrap_or(String::new()))
+	}
+
+	fn next_line(&mut self) -> Option<String> {
+		let mut buffer = [0u8; 4096];
+
+		while self.pending.is_empty() {
+			let size = match self.socket.recv_from(&mut buffer) {
+				Ok((size, _)) => size,
+				Err(_) => return None,
+			};
+			for line in String::from_utf8_lossy(&buffer[..size]).lines() {
+				self.pending.push_back(line.to_string());
+			}
+		}
+
+		self.pending.pop_front()
+	}
+}
+
+#[test]
+fn test_sources() {
+	assert_eq!(SourceConfig::parse("sample"), Ok(SourceConfig::File(SAMPLE_EVENTS.to_string())));
+	assert_eq!(SourceConfig::parse("tcp:localhost:4321"), Ok(SourceConfig::Tcp("localhost:4321".to_string())));
+	assert_eq!(SourceConfig::parse("file:events.log"), Ok(SourceConfig::File("events.log".to_string())));
+	assert!(SourceConfig::parse("file:").is_err());
+	assert!(SourceConfig::parse("rdsd:foo").is_err());
+	assert_eq!(SourceConfig::parse("rds:udp:0.0.0.0:4321"), Ok(SourceConfig::Rds(Box::new(SourceConfig::Udp("0.0.0.0:4321".to_string())))));
+
+	let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+	let address = receiver.local_addr().unwrap();
+	let mut source = UdpSource { socket: receiver, pending: VecDeque::new() };
+
+	let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+	sender.send_to(b"GF evt=803 loc=45112 ext=1 CI=5 dir=0\nGS CI=4 GSI=0 F1=332 F2=2048\n", address).unwrap();
+
+	assert_eq!(source.next_line(), Some("GF evt=803 loc=45112 ext=1 CI=5 dir=0".to_string()));
+	assert_eq!(source.next_line(), Some("GS CI=4 GSI=0 F1=332 F2=2048".to_string()));
+}
+
+#[test]
+fn test_record_and_replay() {
+	let path = ::std::env::temp_dir().join(format!("tmc_recording_{}.log", ::std::process::id()));
+	let path = path.to_str().unwrap().to_string();
+	let _ = ::std::fs::remove_file(&path);
+
+	{
+		let mut recorder = Recorder::open(&path).unwrap();
+		recorder.record_at(1000, "GF evt=803 loc=45112 ext=1 CI=5 dir=0").unwrap();
+		recorder.record_at(1000 + 60 * 1000, "GF evt=101 loc=45113 ext=0 CI=5 dir=1").unwrap();
+	}
+
+	// a minute of events at 1000x speed takes 60ms
+	let config = SourceConfig::Replay { path: path.clone(), speed: 1000.0 };
+	let mut source = config.open().unwrap();
+	let start = Instant::now();
+
+	assert_eq!(source.next_line(), Some("GF evt=803 loc=45112 ext=1 CI=5 dir=0".to_string()));
+	let first = source.received();
+	assert!((first - ::time::get_time().sec).abs() <= 1);
+	assert_eq!(source.next_line(), Some("GF evt=101 loc=45113 ext=0 CI=5 dir=1".to_string()));
+	assert_eq!(source.next_line(), None);
+	assert!(start.elapsed() >= Duration::from_millis(60));
+
+	// without waiting the lines start at the time of the replay and keep their recorded offsets
+	let mut source = SourceConfig::Replay { path: path.clone(), speed: 0.0 }.open().unwrap();
+	let start = Instant::now();
+	source.next_line();
+	let first = source.received();
+	assert!((first - ::time::get_time().sec).abs() <= 1);
+	source.next_line();
+	assert!((source.received() - (first + 60)).abs() <= 1);
+	assert!(start.elapsed() < Duration::from_millis(60));
+
+	assert_eq!(parse_recorded_line("GS CI=4 GSI=0 F1=332 F2=2048"), None);
+	let _ = ::std::fs::remove_file(&path);
+}